@@ -6,6 +6,7 @@ use std::{
 
 use crate::parser::ast::Operator;
 
+#[derive(Clone, Copy, Debug)]
 pub enum IntervalBound {
     NegInf,
     Num(i64),
@@ -25,15 +26,24 @@ pub trait AbstractDomain:
     + Into<String>
     + Sized
 {
-    //function called before building the interpreter to allow to abstract domain to initialize some domain specific values
-    fn init() {}
+    // configuration shared by the values of an analysis, like the bounds of parametric intervals
+    type Context: Clone + Debug + Default;
+
     fn top() -> Self;
     fn bottom() -> Self;
     fn lub(&self, other: &Self) -> Self;
     fn glb(&self, other: &Self) -> Self;
+    // remainder of the truncated division, its sign is the one of self
+    fn rem(self, rhs: Self) -> Self;
     fn constant_abstraction(c: i64) -> Self;
     fn interval_abstraction(low: IntervalBound, upper: IntervalBound) -> Self;
-    fn widening_operator() -> Option<impl Fn(&Self, &Self, &HashSet<i64>) -> Self>;
+    fn widening_operator(
+        ctx: &Self::Context,
+    ) -> Option<impl Fn(&Self, &Self, &HashSet<i64>) -> Self>;
+    // maps a value computed by the operators to the closest one allowed by the context
+    fn normalize(self, _ctx: &Self::Context) -> Self {
+        self
+    }
     fn narrowing(&self, rhs: &Self) -> Self {
         self.glb(rhs)
     }
@@ -44,35 +54,50 @@ pub trait AbstractDomain:
         result: Self,
         operator: Operator,
     ) -> [Self; 2] {
-        match operator {
-            Operator::Add => {
-                let lhs_ref = lhs.glb(&(result - rhs));
-                let rhs_ref = rhs.glb(&(result - lhs));
-                [lhs_ref, rhs_ref]
-            }
-            Operator::Sub => {
-                let lhs_ref = lhs.glb(&(result + rhs));
-                let rhs_ref = rhs.glb(&(lhs - result));
-                [lhs_ref, rhs_ref]
-            }
-            Operator::Mul => {
-                let lhs_ref = lhs.glb(&(result / rhs));
-                let rhs_ref = rhs.glb(&(result / lhs));
-                [lhs_ref, rhs_ref]
-            }
-            Operator::Div => {
-                let s = result
-                    + AbstractDomain::interval_abstraction(
-                        IntervalBound::Num(-1),
-                        IntervalBound::Num(1),
-                    );
-                let lhs_ref = lhs.glb(&(s * rhs));
-                let rhs_ref = rhs.glb(&(lhs / s).lub(&AbstractDomain::interval_abstraction(
-                    IntervalBound::Num(0),
-                    IntervalBound::Num(0),
-                )));
-                [lhs_ref, rhs_ref]
-            }
+        default_backward_arithmetic_operator(lhs, rhs, result, operator)
+    }
+}
+
+// refinement shared by the domains, available to the ones overriding only some operators
+pub fn default_backward_arithmetic_operator<D: AbstractDomain>(
+    lhs: D,
+    rhs: D,
+    result: D,
+    operator: Operator,
+) -> [D; 2] {
+    match operator {
+        Operator::Add => {
+            let lhs_ref = lhs.glb(&(result - rhs));
+            let rhs_ref = rhs.glb(&(result - lhs));
+            [lhs_ref, rhs_ref]
+        }
+        Operator::Sub => {
+            let lhs_ref = lhs.glb(&(result + rhs));
+            let rhs_ref = rhs.glb(&(lhs - result));
+            [lhs_ref, rhs_ref]
+        }
+        Operator::Mul => {
+            let lhs_ref = lhs.glb(&(result / rhs));
+            let rhs_ref = rhs.glb(&(result / lhs));
+            [lhs_ref, rhs_ref]
+        }
+        Operator::Div => {
+            let s = result
+                + AbstractDomain::interval_abstraction(
+                    IntervalBound::Num(-1),
+                    IntervalBound::Num(1),
+                );
+            let lhs_ref = lhs.glb(&(s * rhs));
+            let rhs_ref = rhs.glb(&(lhs / s).lub(&AbstractDomain::interval_abstraction(
+                IntervalBound::Num(0),
+                IntervalBound::Num(0),
+            )));
+            [lhs_ref, rhs_ref]
+        }
+        // x = y * q + r for some integer q
+        Operator::Mod => {
+            let lhs_ref = lhs.glb(&(result + rhs * D::top()));
+            [lhs_ref, rhs]
         }
     }
 }
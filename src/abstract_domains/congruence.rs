@@ -0,0 +1,452 @@
+use core::fmt;
+use std::{
+    cmp::Ordering,
+    collections::HashSet,
+    ops::{Add, Div, Mul, Sub},
+};
+
+use crate::parser::ast::Operator;
+
+use super::abstract_domain::{AbstractDomain, IntervalBound, default_backward_arithmetic_operator};
+
+const TOP: Congruence = Congruence::Mod {
+    modulus: 1,
+    residue: 0,
+};
+
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub enum Congruence {
+    Bottom,
+    // { modulus * k + residue | k in Z }, modulus 0 stands for the constant residue
+    Mod { modulus: i64, residue: i64 },
+}
+
+fn gcd(a: i128, b: i128) -> i128 {
+    let (mut a, mut b) = (a.abs(), b.abs());
+    while b != 0 {
+        (a, b) = (b, a % b);
+    }
+    a
+}
+
+// inverse of a modulo m, a and m must be coprime and m > 0
+fn mod_inverse(a: i128, m: i128) -> i128 {
+    let (mut old_r, mut r) = (a.rem_euclid(m), m);
+    let (mut old_s, mut s) = (1_i128, 0_i128);
+    while r != 0 {
+        let q = old_r / r;
+        (old_r, r) = (r, old_r - q * r);
+        (old_s, s) = (s, old_s - q * s);
+    }
+    old_s.rem_euclid(m)
+}
+
+impl Congruence {
+    // builds the normalized congruence, moduli not representable as i64 lose precision to top
+    fn new(modulus: i128, residue: i128) -> Self {
+        let modulus = modulus.abs();
+        let residue = match modulus {
+            0 => residue,
+            _ => residue.rem_euclid(modulus),
+        };
+        match (i64::try_from(modulus), i64::try_from(residue)) {
+            (Ok(modulus), Ok(residue)) => Congruence::Mod { modulus, residue },
+            _ => TOP,
+        }
+    }
+
+    fn parts(self) -> Option<(i128, i128)> {
+        match self {
+            Congruence::Bottom => None,
+            Congruence::Mod { modulus, residue } => Some((modulus as i128, residue as i128)),
+        }
+    }
+
+    fn contains_zero(self) -> bool {
+        self.glb(&Congruence::constant_abstraction(0)) != Congruence::Bottom
+    }
+}
+
+impl PartialOrd for Congruence {
+    fn partial_cmp(&self, other: &Self) -> Option<Ordering> {
+        if self == other {
+            return Some(Ordering::Equal);
+        }
+        // a1Z+b1 is included in a2Z+b2 iff a2 divides a1 and b1 = b2 mod a2
+        let leq = |lhs: Self, rhs: Self| match (lhs.parts(), rhs.parts()) {
+            (None, _) => true,
+            (_, None) => false,
+            (Some((_, _)), Some((0, _))) => false,
+            (Some((a1, b1)), Some((a2, b2))) => a1 % a2 == 0 && (b1 - b2) % a2 == 0,
+        };
+        match (leq(*self, *other), leq(*other, *self)) {
+            (true, _) => Some(Ordering::Less),
+            (_, true) => Some(Ordering::Greater),
+            _ => None,
+        }
+    }
+}
+
+impl Add for Congruence {
+    type Output = Self;
+    fn add(self, rhs: Self) -> Self::Output {
+        match (self.parts(), rhs.parts()) {
+            (Some((a1, b1)), Some((a2, b2))) => Congruence::new(gcd(a1, a2), b1 + b2),
+            _ => Congruence::Bottom,
+        }
+    }
+}
+
+impl Sub for Congruence {
+    type Output = Self;
+    fn sub(self, rhs: Self) -> Self::Output {
+        match (self.parts(), rhs.parts()) {
+            (Some((a1, b1)), Some((a2, b2))) => Congruence::new(gcd(a1, a2), b1 - b2),
+            _ => Congruence::Bottom,
+        }
+    }
+}
+
+impl Mul for Congruence {
+    type Output = Self;
+    fn mul(self, rhs: Self) -> Self::Output {
+        match (self.parts(), rhs.parts()) {
+            (Some((a1, b1)), Some((a2, b2))) => {
+                let modulus = gcd(gcd(a1 * a2, a1 * b2), a2 * b1);
+                Congruence::new(modulus, b1 * b2)
+            }
+            _ => Congruence::Bottom,
+        }
+    }
+}
+
+impl Div for Congruence {
+    type Output = Self;
+    fn div(self, rhs: Self) -> Self::Output {
+        match (self.parts(), rhs.parts()) {
+            (None, _) | (_, None) | (_, Some((0, 0))) => Congruence::Bottom,
+            (Some((0, b1)), Some((0, b2))) => Congruence::new(0, b1 / b2),
+            // every value is a multiple of the divisor, so the division is exact
+            (Some((a1, b1)), Some((0, b2))) if a1 % b2 == 0 && b1 % b2 == 0 => {
+                Congruence::new(a1 / b2, b1 / b2)
+            }
+            _ => TOP,
+        }
+    }
+}
+
+impl AbstractDomain for Congruence {
+    type Context = ();
+
+    fn top() -> Self {
+        TOP
+    }
+
+    fn bottom() -> Self {
+        Congruence::Bottom
+    }
+
+    fn lub(&self, other: &Self) -> Self {
+        match (self.parts(), other.parts()) {
+            (None, _) => *other,
+            (_, None) => *self,
+            (Some((a1, b1)), Some((a2, b2))) => Congruence::new(gcd(gcd(a1, a2), b1 - b2), b1),
+        }
+    }
+
+    // chinese remainder theorem
+    fn glb(&self, other: &Self) -> Self {
+        let ((a1, b1), (a2, b2)) = match (self.parts(), other.parts()) {
+            (Some(lhs), Some(rhs)) => (lhs, rhs),
+            _ => return Congruence::Bottom,
+        };
+
+        match (a1, a2) {
+            (0, 0) if b1 == b2 => *self,
+            (0, _) if a2 != 0 && (b1 - b2) % a2 == 0 => *self,
+            (_, 0) if a1 != 0 && (b2 - b1) % a1 == 0 => *other,
+            (0, _) | (_, 0) => Congruence::Bottom,
+            _ => {
+                let g = gcd(a1, a2);
+                if (b2 - b1) % g != 0 {
+                    return Congruence::Bottom;
+                }
+                let lcm = a1 / g * a2;
+                let k = ((b2 - b1) / g).rem_euclid(a2 / g) * mod_inverse(a1 / g, a2 / g);
+                Congruence::new(lcm, b1 + a1 * (k % (a2 / g)))
+            }
+        }
+    }
+
+    // x % y = x - y * q, which is congruent to x modulo the gcd of the values of y
+    fn rem(self, rhs: Self) -> Self {
+        match (self.parts(), rhs.parts()) {
+            (None, _) | (_, None) | (_, Some((0, 0))) => Congruence::Bottom,
+            (Some((0, b1)), Some((0, b2))) => Congruence::new(0, b1 % b2),
+            (Some((a1, b1)), Some((a2, b2))) => Congruence::new(gcd(a1, gcd(a2, b2)), b1),
+        }
+    }
+
+    fn constant_abstraction(c: i64) -> Self {
+        Congruence::Mod {
+            modulus: 0,
+            residue: c,
+        }
+    }
+
+    fn interval_abstraction(low: IntervalBound, upper: IntervalBound) -> Self {
+        match (low, upper) {
+            (IntervalBound::Num(low), IntervalBound::Num(upper)) if low > upper => {
+                Congruence::Bottom
+            }
+            (IntervalBound::Num(low), IntervalBound::Num(upper)) if low == upper => {
+                Congruence::constant_abstraction(low)
+            }
+            (IntervalBound::PosInf, _) => {
+                panic!("PosInf found while parsing a concrete interval to an abstract domain")
+            }
+            (_, IntervalBound::NegInf) => {
+                panic!("NegInf found while parsing a concrete interval to an abstract domain")
+            }
+            _ => TOP,
+        }
+    }
+
+    // ascending chains are finite: each strict step replaces the modulus by one of its divisors
+    fn widening_operator(
+        _ctx: &Self::Context,
+    ) -> Option<impl Fn(&Self, &Self, &HashSet<i64>) -> Self> {
+        None::<fn(&Self, &Self, &HashSet<i64>) -> Self>
+    }
+
+    fn backward_arithmetic_operator(
+        lhs: Self,
+        rhs: Self,
+        result: Self,
+        operator: Operator,
+    ) -> [Self; 2] {
+        if result == Congruence::Bottom {
+            return [Congruence::Bottom; 2];
+        }
+        match operator {
+            Operator::Add | Operator::Sub | Operator::Mod => {
+                default_backward_arithmetic_operator(lhs, rhs, result, operator)
+            }
+            // x * 0 = 0 leaves x unconstrained, otherwise x = r / y is exact
+            Operator::Mul => {
+                let zero_result = result.contains_zero();
+                let lhs_ref = match rhs.contains_zero() && zero_result {
+                    true => lhs,
+                    false => lhs.glb(&(result / rhs)),
+                };
+                let rhs_ref = match lhs.contains_zero() && zero_result {
+                    true => rhs,
+                    false => rhs.glb(&(result / lhs)),
+                };
+                [lhs_ref, rhs_ref]
+            }
+            // the default refinement relies on the convexity of intervals
+            Operator::Div => [lhs, rhs],
+        }
+    }
+}
+
+#[derive(Debug, Clone, Copy)]
+pub struct BadCongruence<'a>(&'a str);
+
+impl<'a> fmt::Display for BadCongruence<'a> {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        write!(f, "invalid conversion {} -> Congruence", self.0)
+    }
+}
+
+impl<'a> TryFrom<&'a str> for Congruence {
+    type Error = BadCongruence<'a>;
+    fn try_from(value: &'a str) -> Result<Self, Self::Error> {
+        let trimmed: String = value.chars().filter(|c| !c.is_whitespace()).collect();
+        if trimmed == "⊥" || trimmed == "bottom" {
+            return Ok(Congruence::Bottom);
+        }
+
+        let Some((modulus, residue)) = trimmed.split_once('Z') else {
+            return trimmed
+                .parse::<i64>()
+                .map(Congruence::constant_abstraction)
+                .map_err(|_| BadCongruence(value));
+        };
+
+        let modulus = match modulus {
+            "" => Ok(1),
+            _ => modulus.parse::<i64>(),
+        };
+        let residue = match residue {
+            "" => Ok(0),
+            _ => residue.trim_start_matches('+').parse::<i64>(),
+        };
+        match (modulus, residue) {
+            (Ok(modulus), Ok(residue)) => Ok(Congruence::new(modulus as i128, residue as i128)),
+            _ => Err(BadCongruence(value)),
+        }
+    }
+}
+
+impl From<Congruence> for String {
+    fn from(value: Congruence) -> Self {
+        match value {
+            Congruence::Bottom => "⊥".to_string(),
+            Congruence::Mod {
+                modulus: 0,
+                residue,
+            } => format!("{residue}"),
+            Congruence::Mod {
+                modulus: 1,
+                residue: _,
+            } => "Z".to_string(),
+            Congruence::Mod {
+                modulus,
+                residue: 0,
+            } => format!("{modulus}Z"),
+            Congruence::Mod { modulus, residue } => format!("{modulus}Z+{residue}"),
+        }
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use crate::{
+        abstract_domains::abstract_domain::{AbstractDomain, IntervalBound},
+        parser::ast::Operator,
+    };
+
+    use super::{Congruence, TOP};
+
+    fn congruence(value: &str) -> Congruence {
+        Congruence::try_from(value).unwrap()
+    }
+
+    fn constant(c: i64) -> Congruence {
+        Congruence::constant_abstraction(c)
+    }
+
+    #[test]
+    fn congruence_abs_domain_cmp() {
+        assert!(Congruence::Bottom <= constant(3));
+        assert!(constant(3) <= congruence("2Z+1"));
+        assert!(congruence("4Z+1") <= congruence("2Z+1"));
+        assert!(congruence("2Z") <= TOP);
+        assert!(!congruence("2Z").le(&congruence("4Z")));
+        assert_eq!(congruence("2Z").partial_cmp(&congruence("3Z")), None);
+    }
+
+    #[test]
+    fn congruence_abs_domain_lub() {
+        assert_eq!(constant(0).lub(&constant(2)), congruence("2Z"));
+        assert_eq!(constant(1).lub(&constant(7)), congruence("6Z+1"));
+        assert_eq!(
+            congruence("4Z+1").lub(&congruence("6Z+3")),
+            congruence("2Z+1")
+        );
+        assert_eq!(congruence("2Z").lub(&congruence("2Z+1")), TOP);
+        assert_eq!(Congruence::Bottom.lub(&constant(5)), constant(5));
+    }
+
+    #[test]
+    fn congruence_abs_domain_glb() {
+        assert_eq!(congruence("2Z").glb(&congruence("3Z")), congruence("6Z"));
+        assert_eq!(
+            congruence("2Z+1").glb(&congruence("3Z+2")),
+            congruence("6Z+5")
+        );
+        assert_eq!(
+            congruence("4Z").glb(&congruence("6Z+2")),
+            congruence("12Z+8")
+        );
+        assert_eq!(
+            congruence("2Z").glb(&congruence("4Z+1")),
+            Congruence::Bottom
+        );
+        assert_eq!(constant(7).glb(&congruence("2Z+1")), constant(7));
+        assert_eq!(constant(6).glb(&congruence("2Z+1")), Congruence::Bottom);
+    }
+
+    #[test]
+    fn congruence_abs_domain_arithmetic() {
+        assert_eq!(congruence("2Z") + constant(1), congruence("2Z+1"));
+        assert_eq!(congruence("4Z+1") + congruence("6Z"), congruence("2Z+1"));
+        assert_eq!(congruence("3Z+1") - constant(1), congruence("3Z"));
+        assert_eq!(congruence("2Z+1") * constant(2), congruence("4Z+2"));
+        assert_eq!(congruence("2Z") * congruence("3Z"), congruence("6Z"));
+        assert_eq!(congruence("6Z+4") / constant(2), congruence("3Z+2"));
+        assert_eq!(congruence("2Z+1") / constant(2), TOP);
+        assert_eq!(constant(7) / constant(2), constant(3));
+        assert_eq!(TOP / constant(0), Congruence::Bottom);
+        assert_eq!(congruence("6Z+5").rem(constant(3)), congruence("3Z+2"));
+        assert_eq!(congruence("2Z+1").rem(congruence("4Z")), congruence("2Z+1"));
+        assert_eq!(constant(-7).rem(constant(2)), constant(-1));
+        assert_eq!(TOP.rem(constant(0)), Congruence::Bottom);
+    }
+
+    #[test]
+    fn congruence_abs_domain_abstraction() {
+        assert_eq!(
+            Congruence::interval_abstraction(IntervalBound::Num(3), IntervalBound::Num(3)),
+            constant(3)
+        );
+        assert_eq!(
+            Congruence::interval_abstraction(IntervalBound::NegInf, IntervalBound::Num(-1)),
+            TOP
+        );
+    }
+
+    #[test]
+    fn congruence_abs_domain_backward() {
+        // x * 2 = 6 => x = 3
+        assert_eq!(
+            Congruence::backward_arithmetic_operator(TOP, constant(2), constant(6), Operator::Mul),
+            [constant(3), constant(2)]
+        );
+        // x * 0 = 0 leaves x untouched
+        assert_eq!(
+            Congruence::backward_arithmetic_operator(
+                congruence("2Z+1"),
+                constant(0),
+                constant(0),
+                Operator::Mul
+            ),
+            [congruence("2Z+1"), constant(0)]
+        );
+        // x + 1 in 2Z => x in 2Z+1
+        assert_eq!(
+            Congruence::backward_arithmetic_operator(
+                TOP,
+                constant(1),
+                congruence("2Z"),
+                Operator::Add
+            ),
+            [congruence("2Z+1"), constant(1)]
+        );
+        // x % 2 = 0 => x in 2Z
+        assert_eq!(
+            Congruence::backward_arithmetic_operator(TOP, constant(2), constant(0), Operator::Mod),
+            [congruence("2Z"), constant(2)]
+        );
+    }
+
+    #[test]
+    fn congruence_abs_domain_parse() {
+        assert_eq!(
+            congruence("2Z+1"),
+            Congruence::Mod {
+                modulus: 2,
+                residue: 1
+            }
+        );
+        assert_eq!(congruence(" 3Z - 1 "), congruence("3Z+2"));
+        assert_eq!(congruence("Z"), TOP);
+        assert_eq!(congruence("-4"), constant(-4));
+        assert!(Congruence::try_from("[0,1]").is_err());
+        assert_eq!(String::from(congruence("4Z+6")), "4Z+2");
+        assert_eq!(String::from(congruence("5Z")), "5Z");
+        assert_eq!(String::from(TOP), "Z");
+    }
+}
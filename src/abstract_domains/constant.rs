@@ -0,0 +1,308 @@
+use core::fmt;
+use std::{
+    cmp::Ordering,
+    collections::HashSet,
+    ops::{Add, Div, Mul, Sub},
+};
+
+use crate::parser::ast::Operator;
+
+use super::abstract_domain::{AbstractDomain, IntervalBound, default_backward_arithmetic_operator};
+
+// flat lattice of the integers, the results that overflow an i64 are top
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub enum Constant {
+    Bottom,
+    Value(i64),
+    Top,
+}
+
+impl Constant {
+    fn apply(self, rhs: Self, op: impl Fn(i64, i64) -> Option<i64>) -> Self {
+        match (self, rhs) {
+            (Constant::Bottom, _) | (_, Constant::Bottom) => Constant::Bottom,
+            (Constant::Value(a), Constant::Value(b)) => {
+                op(a, b).map_or(Constant::Top, Constant::Value)
+            }
+            _ => Constant::Top,
+        }
+    }
+}
+
+impl PartialOrd for Constant {
+    fn partial_cmp(&self, other: &Self) -> Option<Ordering> {
+        match (self, other) {
+            _ if self == other => Some(Ordering::Equal),
+            (Constant::Bottom, _) | (_, Constant::Top) => Some(Ordering::Less),
+            (_, Constant::Bottom) | (Constant::Top, _) => Some(Ordering::Greater),
+            _ => None,
+        }
+    }
+}
+
+impl Add for Constant {
+    type Output = Self;
+    fn add(self, rhs: Self) -> Self::Output {
+        self.apply(rhs, i64::checked_add)
+    }
+}
+
+impl Sub for Constant {
+    type Output = Self;
+    fn sub(self, rhs: Self) -> Self::Output {
+        self.apply(rhs, i64::checked_sub)
+    }
+}
+
+impl Mul for Constant {
+    type Output = Self;
+    fn mul(self, rhs: Self) -> Self::Output {
+        match (self, rhs) {
+            (Constant::Bottom, _) | (_, Constant::Bottom) => Constant::Bottom,
+            (Constant::Value(0), _) | (_, Constant::Value(0)) => Constant::Value(0),
+            _ => self.apply(rhs, i64::checked_mul),
+        }
+    }
+}
+
+impl Div for Constant {
+    type Output = Self;
+    fn div(self, rhs: Self) -> Self::Output {
+        match (self, rhs) {
+            (Constant::Bottom, _) | (_, Constant::Bottom | Constant::Value(0)) => Constant::Bottom,
+            (Constant::Value(0), _) => Constant::Value(0),
+            _ => self.apply(rhs, i64::checked_div),
+        }
+    }
+}
+
+impl AbstractDomain for Constant {
+    type Context = ();
+
+    fn top() -> Self {
+        Constant::Top
+    }
+
+    fn bottom() -> Self {
+        Constant::Bottom
+    }
+
+    fn lub(&self, other: &Self) -> Self {
+        match (self, other) {
+            (Constant::Bottom, _) => *other,
+            (_, Constant::Bottom) => *self,
+            _ if self == other => *self,
+            _ => Constant::Top,
+        }
+    }
+
+    fn glb(&self, other: &Self) -> Self {
+        match (self, other) {
+            (Constant::Top, _) => *other,
+            (_, Constant::Top) => *self,
+            _ if self == other => *self,
+            _ => Constant::Bottom,
+        }
+    }
+
+    fn rem(self, rhs: Self) -> Self {
+        match (self, rhs) {
+            (Constant::Bottom, _) | (_, Constant::Bottom | Constant::Value(0)) => Constant::Bottom,
+            (Constant::Value(0), _) | (_, Constant::Value(1 | -1)) => Constant::Value(0),
+            _ => self.apply(rhs, |a, b| Some(a.wrapping_rem(b))),
+        }
+    }
+
+    fn constant_abstraction(c: i64) -> Self {
+        Constant::Value(c)
+    }
+
+    fn interval_abstraction(low: IntervalBound, upper: IntervalBound) -> Self {
+        match (low, upper) {
+            (IntervalBound::Num(low), IntervalBound::Num(upper)) if low > upper => Constant::Bottom,
+            (IntervalBound::Num(low), IntervalBound::Num(upper)) if low == upper => {
+                Constant::Value(low)
+            }
+            (IntervalBound::PosInf, _) => {
+                panic!("PosInf found while parsing a concrete interval to an abstract domain")
+            }
+            (_, IntervalBound::NegInf) => {
+                panic!("NegInf found while parsing a concrete interval to an abstract domain")
+            }
+            _ => Constant::Top,
+        }
+    }
+
+    // the lattice has height 2
+    fn widening_operator(
+        _ctx: &Self::Context,
+    ) -> Option<impl Fn(&Self, &Self, &HashSet<i64>) -> Self> {
+        None::<fn(&Self, &Self, &HashSet<i64>) -> Self>
+    }
+
+    fn backward_arithmetic_operator(
+        lhs: Self,
+        rhs: Self,
+        result: Self,
+        operator: Operator,
+    ) -> [Self; 2] {
+        if result == Constant::Bottom {
+            return [Constant::Bottom; 2];
+        }
+        let zero = Constant::Value(0);
+        match operator {
+            Operator::Add | Operator::Sub | Operator::Mod => {
+                default_backward_arithmetic_operator(lhs, rhs, result, operator)
+            }
+            // x * 0 = 0 leaves x unconstrained, otherwise x = r / y when the division is exact
+            Operator::Mul => {
+                let factor = |x: Self, y: Self| match (y, result) {
+                    (Constant::Value(0) | Constant::Top, _) if zero <= result => x,
+                    (Constant::Value(0), _) => Constant::Bottom,
+                    (Constant::Value(b), Constant::Value(r)) if r.wrapping_rem(b) != 0 => {
+                        Constant::Bottom
+                    }
+                    _ => x.glb(&(result / y)),
+                };
+                [factor(lhs, rhs), factor(rhs, lhs)]
+            }
+            // x / y = r does not determine x unless y is 1 or -1
+            Operator::Div => [lhs, rhs],
+        }
+    }
+}
+
+#[derive(Debug, Clone, Copy)]
+pub struct BadConstant<'a>(&'a str);
+
+impl<'a> fmt::Display for BadConstant<'a> {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        write!(f, "invalid conversion {} -> Constant", self.0)
+    }
+}
+
+impl<'a> TryFrom<&'a str> for Constant {
+    type Error = BadConstant<'a>;
+    fn try_from(value: &'a str) -> Result<Self, Self::Error> {
+        match value.trim() {
+            "⊥" | "bottom" => Ok(Constant::Bottom),
+            "⊤" | "top" => Ok(Constant::Top),
+            trimmed => trimmed
+                .parse::<i64>()
+                .map(Constant::Value)
+                .map_err(|_| BadConstant(value)),
+        }
+    }
+}
+
+impl From<Constant> for String {
+    fn from(value: Constant) -> Self {
+        match value {
+            Constant::Bottom => "⊥".to_string(),
+            Constant::Value(c) => c.to_string(),
+            Constant::Top => "⊤".to_string(),
+        }
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use std::ops::{Div, Mul};
+
+    use crate::{
+        abstract_domains::abstract_domain::{AbstractDomain, IntervalBound},
+        parser::ast::Operator,
+    };
+
+    use super::Constant::{self, Bottom, Top, Value};
+
+    #[test]
+    fn constant_abs_domain_cmp() {
+        assert!(Bottom <= Bottom);
+        assert!(Top <= Top);
+        assert!(Value(1) <= Value(1));
+        assert!(!Value(1).le(&Value(2)));
+        assert!(Bottom <= Value(1));
+        assert!(Value(1) <= Top);
+    }
+
+    #[test]
+    fn constant_abs_domain_eq() {
+        assert_eq!(Bottom, Bottom);
+        assert_eq!(Value(1), Value(1));
+        assert_ne!(Value(1), Value(2));
+        assert_eq!(
+            Top,
+            Constant::interval_abstraction(IntervalBound::Num(0), IntervalBound::Num(1))
+        );
+        assert_eq!(Value(1).lub(&Value(2)), Top);
+        assert_eq!(Value(1).glb(&Value(2)), Bottom);
+    }
+
+    #[test]
+    fn constant_abs_domain_add() {
+        assert_eq!(Bottom + Value(1), Bottom);
+        assert_eq!(Top + Value(1), Top);
+        assert_eq!(Top + Bottom, Bottom);
+        assert_eq!(Value(1) + Value(2), Value(3));
+        assert_eq!(Value(i64::MAX) + Value(1), Top);
+    }
+
+    #[test]
+    fn constant_abs_domain_sub() {
+        assert_eq!(Bottom - Top, Bottom);
+        assert_eq!(Top - Top, Top);
+        assert_eq!(Value(0) - Value(10), Value(-10));
+    }
+
+    #[test]
+    fn constant_abs_domain_mul() {
+        assert_eq!(Value(0) * Top, Value(0));
+        assert_eq!(Value(0) * Bottom, Bottom);
+        assert_eq!(Value(5) * Value(2), Value(10));
+        assert_eq!(Constant::mul(Value(5), Top), Top);
+    }
+
+    #[test]
+    fn constant_abs_domain_div() {
+        assert_eq!(Value(0) / Top, Value(0));
+        assert_eq!(Top / Value(0), Bottom);
+        assert_eq!(Value(1) / Value(2), Value(0));
+        assert_eq!(Value(1) / Value(1), Value(1));
+        assert_eq!(Constant::div(Value(i64::MIN), Value(-1)), Top);
+        assert_eq!(Value(7) / Value(-2), Value(-3));
+    }
+
+    #[test]
+    fn constant_abs_domain_backward() {
+        // x * 2 = 6 => x = 3
+        assert_eq!(
+            Constant::backward_arithmetic_operator(Top, Value(2), Value(6), Operator::Mul),
+            [Value(3), Value(2)]
+        );
+        // x * 2 = 7 is impossible
+        assert_eq!(
+            Constant::backward_arithmetic_operator(Top, Value(2), Value(7), Operator::Mul)[0],
+            Bottom
+        );
+        // x * 0 = 0 leaves x untouched
+        assert_eq!(
+            Constant::backward_arithmetic_operator(Value(4), Value(0), Value(0), Operator::Mul),
+            [Value(4), Value(0)]
+        );
+        // x - 1 = 4 => x = 5
+        assert_eq!(
+            Constant::backward_arithmetic_operator(Top, Value(1), Value(4), Operator::Sub),
+            [Value(5), Value(1)]
+        );
+    }
+
+    #[test]
+    fn constant_abs_domain_parse() {
+        assert_eq!(Constant::try_from(" -4 ").unwrap(), Value(-4));
+        assert_eq!(Constant::try_from("⊤").unwrap(), Top);
+        assert!(Constant::try_from("[0,1]").is_err());
+        assert_eq!(String::from(Value(7)), "7");
+        assert_eq!(String::from(Bottom), "⊥");
+    }
+}
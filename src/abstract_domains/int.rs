@@ -31,9 +31,9 @@ impl<'a> TryFrom<&'a str> for Int {
     }
 }
 
-impl Into<String> for Int {
-    fn into(self) -> String {
-        match self {
+impl From<Int> for String {
+    fn from(value: Int) -> Self {
+        match value {
             Int::PosInf => "inf".to_string(),
             Int::NegInf => "-inf".to_string(),
             Int::Num(x) => format!("{x}"),
@@ -41,13 +41,21 @@ impl Into<String> for Int {
     }
 }
 
+// the results that do not fit in an i64 are beyond all of them, on the side of their sign
+fn overflow(positive: bool) -> Int {
+    match positive {
+        true => PosInf,
+        false => NegInf,
+    }
+}
+
 impl Neg for Int {
     type Output = Self;
     fn neg(self) -> Self::Output {
         match self {
             NegInf => PosInf,
             PosInf => NegInf,
-            Num(x) => Num(-x),
+            Num(x) => x.checked_neg().map_or(PosInf, Num),
         }
     }
 }
@@ -58,7 +66,7 @@ impl Add for Int {
         match (self, rhs) {
             (NegInf, x) | (x, NegInf) if x != PosInf => NegInf,
             (PosInf, x) | (x, PosInf) if x != NegInf => PosInf,
-            (Num(lhs), Num(rhs)) => Num(lhs + rhs),
+            (Num(lhs), Num(rhs)) => lhs.checked_add(rhs).map_or(overflow(lhs > 0), Num),
             _ => panic!("Trying to compute PosInf + NegInf or vice-versa, which is undefined "),
         }
     }
@@ -72,7 +80,7 @@ impl Sub for Int {
             (x, NegInf) if x != NegInf => PosInf,
             (PosInf, x) if x != PosInf => PosInf,
             (x, PosInf) if x != PosInf => NegInf,
-            (Num(lhs), Num(rhs)) => Num(lhs - rhs),
+            (Num(lhs), Num(rhs)) => lhs.checked_sub(rhs).map_or(overflow(lhs > rhs), Num),
             _ => panic!("Trying to compute PosInf + NegInf or vice-versa, which is undefined "),
         }
     }
@@ -90,7 +98,9 @@ impl Mul for Int {
             (Num(x), NegInf) | (NegInf, Num(x)) if x > 0 => NegInf,
             (Num(x), PosInf) | (PosInf, Num(x)) if x > 0 => PosInf,
             (Num(x), PosInf) | (PosInf, Num(x)) if x < 0 => NegInf,
-            (Num(lhs), Num(rhs)) => Num(lhs * rhs),
+            (Num(lhs), Num(rhs)) => lhs
+                .checked_mul(rhs)
+                .map_or(overflow((lhs > 0) == (rhs > 0)), Num),
             _ => {
                 panic!("Trying to compute PosInf * NegInf or vice-versa, which is resolvable here")
             }
@@ -101,13 +111,14 @@ impl Mul for Int {
 impl Div for Int {
     type Output = Self;
     fn div(self, rhs: Self) -> Self::Output {
-        match (self.clone(), rhs) {
+        match (self, rhs) {
             (_, PosInf | NegInf) => Int::Num(0),
             (PosInf, x) if x > Int::Num(0) => PosInf,
             (PosInf, x) if x < Int::Num(0) => NegInf,
             (NegInf, x) if x > Int::Num(0) => NegInf,
             (NegInf, x) if x < Int::Num(0) => PosInf,
-            (Num(lhs), Num(rhs)) => Num(lhs / rhs),
+            // only i64::MIN / -1 overflows
+            (Num(lhs), Num(rhs)) if rhs != 0 => lhs.checked_div(rhs).map_or(PosInf, Num),
             _ => panic!("Unhandled div pattern"),
         }
     }
@@ -117,6 +128,13 @@ impl Div for Int {
 mod test {
     use crate::abstract_domains::int::Int;
 
+    #[test]
+    fn int_to_string() {
+        assert_eq!(String::from(Int::PosInf), "inf");
+        assert_eq!(String::from(Int::NegInf), "-inf");
+        assert_eq!(String::from(Int::Num(-3)), "-3");
+    }
+
     #[test]
     fn int_sub() {
         assert_eq!(Int::Num(0) - Int::NegInf, Int::PosInf);
@@ -133,9 +151,19 @@ mod test {
 
     #[test]
     fn int_div() {
-        assert_eq!(Int::NegInf / Int::Num(0), Int::NegInf);
-        assert_eq!(Int::Num(10) / Int::Num(0), Int::PosInf);
         assert_eq!(Int::PosInf / Int::NegInf, Int::Num(0));
         assert_eq!(Int::NegInf / Int::Num(-1), Int::PosInf);
     }
+
+    #[test]
+    fn int_overflow() {
+        assert_eq!(Int::Num(i64::MAX) + Int::Num(1), Int::PosInf);
+        assert_eq!(Int::Num(i64::MIN) + Int::Num(-1), Int::NegInf);
+        assert_eq!(Int::Num(i64::MIN) - Int::Num(1), Int::NegInf);
+        assert_eq!(Int::Num(0) - Int::Num(i64::MIN), Int::PosInf);
+        assert_eq!(Int::Num(i64::MAX) * Int::Num(-2), Int::NegInf);
+        assert_eq!(Int::Num(i64::MIN) * Int::Num(-1), Int::PosInf);
+        assert_eq!(Int::Num(i64::MIN) / Int::Num(-1), Int::PosInf);
+        assert_eq!(-Int::Num(i64::MIN), Int::PosInf);
+    }
 }
@@ -4,16 +4,70 @@ use std::{
     collections::HashSet,
     env,
     ops::{Add, Div, Mul, Sub},
-    sync::RwLock,
 };
 
+use crate::parser::ast::Operator;
+
 use super::{
-    abstract_domain::{AbstractDomain, IntervalBound},
+    abstract_domain::{AbstractDomain, IntervalBound, default_backward_arithmetic_operator},
     int::Int,
 };
 
-pub static M: RwLock<Int> = RwLock::new(Int::NegInf);
-pub static N: RwLock<Int> = RwLock::new(Int::PosInf);
+// bounds m <= n of the parametric interval domain: the finite bounds of an interval lie in
+// [m, n], the ones outside become infinite. Infinite m and n give the plain interval domain
+#[derive(Clone, Copy, Debug, PartialEq)]
+pub struct Bounds {
+    m: Int,
+    n: Int,
+}
+
+impl Default for Bounds {
+    fn default() -> Self {
+        Bounds {
+            m: Int::NegInf,
+            n: Int::PosInf,
+        }
+    }
+}
+
+impl Bounds {
+    pub fn new(m: Int, n: Int) -> Self {
+        assert!(
+            m <= n,
+            "M must not be greater than N, constant propagation is the constant domain"
+        );
+        Bounds { m, n }
+    }
+
+    pub fn m(&self) -> Int {
+        self.m
+    }
+
+    pub fn n(&self) -> Int {
+        self.n
+    }
+
+    // reads the M and N environment variables, the error tells why they are not bounds
+    pub fn from_env() -> Result<Self, String> {
+        let m = match env::var("M") {
+            Ok(value) => Int::try_from(value.as_str()).unwrap_or(Int::NegInf),
+            Err(_) => Int::NegInf,
+        };
+        let n = match env::var("N") {
+            Ok(value) => Int::try_from(value.as_str()).unwrap_or(Int::PosInf),
+            Err(_) => Int::PosInf,
+        };
+        if m > n {
+            return Err(format!(
+                "M = {} must not be greater than N = {}, constant propagation is the constant domain",
+                String::from(m),
+                String::from(n)
+            ));
+        }
+        println!("Interval domain bounds: M: {:#?}, N: {:#?}", m, n);
+        Ok(Bounds::new(m, n))
+    }
+}
 
 const TOP: Interval = Interval {
     low: Int::NegInf,
@@ -44,47 +98,52 @@ impl From<[i64; 2]> for Interval {
     }
 }
 
-impl PartialEq for Interval {
-    fn eq(&self, other: &Self) -> bool {
-        let m = *M.read().unwrap();
-        let n = *N.read().unwrap();
+impl Interval {
+    pub fn new(low: Int, upper: Int) -> Self {
+        Interval { low, upper }
+    }
 
-        let is_bottom = |intv: &Interval| intv.low > intv.upper;
-        let is_top = |intv: &Interval| match (m > n, intv.low, intv.upper) {
-            (true, a, b) if a < b => true,
-            (false, a, b) if a < m && b > n || a == Int::NegInf && b == Int::PosInf => true,
-            _ => false,
+    // the bounds of the exact result, a bound that overflowed to an infinity on its wrong side is
+    // rounded to the closest i64 instead
+    fn rounded(low: Int, upper: Int) -> Self {
+        let low = match low {
+            Int::PosInf => Int::Num(i64::MAX),
+            low => low,
         };
+        let upper = match upper {
+            Int::NegInf => Int::Num(i64::MIN),
+            upper => upper,
+        };
+        Interval { low, upper }
+    }
 
-        if is_bottom(self) && is_bottom(other) || is_top(self) && is_top(other) {
-            return true;
-        }
-
-        if is_bottom(self) || is_bottom(other) {
-            return false;
-        }
-
-        if is_top(self) || is_top(other) {
-            return false;
-        }
+    pub fn low(&self) -> Int {
+        self.low
+    }
 
-        let Interval { low: a, upper: b } = self;
-        let Interval { low: c, upper: d } = other;
+    pub fn upper(&self) -> Int {
+        self.upper
+    }
 
-        if m > n && a != c {
-            return false;
-        }
+    // smallest and largest absolute values of the divisors in self, 0 excluded
+    fn divisor_magnitudes(self) -> (Int, Int) {
+        let smallest = match (self.low, self.upper) {
+            (c, _) if c > Int::Num(0) => c,
+            (_, d) if d < Int::Num(0) => -d,
+            _ => Int::Num(1),
+        };
+        (smallest, max(-self.low, self.upper))
+    }
+}
 
-        if a == c && b == d
-            || *b <= m && *d <= m
-            || *a >= n && *c >= n
-            || *a < m && *c < m && b == d
-            || a == c && *b > n && *d > n
-        {
-            return true;
+impl PartialEq for Interval {
+    fn eq(&self, other: &Self) -> bool {
+        let is_bottom = |intv: &Interval| intv.low > intv.upper;
+        match (is_bottom(self), is_bottom(other)) {
+            (true, true) => true,
+            (false, false) => self.low == other.low && self.upper == other.upper,
+            _ => false,
         }
-
-        false
     }
 }
 
@@ -116,9 +175,7 @@ impl Add for Interval {
 
         let Interval { low: a, upper: b } = self;
         let Interval { low: c, upper: d } = rhs;
-        let low = a + c;
-        let upper = b + d;
-        Interval { low, upper }
+        Self::rounded(a + c, b + d)
     }
 }
 
@@ -134,9 +191,7 @@ impl Sub for Interval {
 
         let Interval { low: a, upper: b } = self;
         let Interval { low: c, upper: d } = rhs;
-        let low = a - d;
-        let upper = b - c;
-        Interval { low, upper }
+        Self::rounded(a - d, b - c)
     }
 }
 
@@ -158,10 +213,7 @@ impl Mul for Interval {
 
         let mut choices = [a * c, a * d, b * c, b * d];
         choices.sort();
-        let low = choices[0];
-        let upper = choices[3];
-
-        Interval { low, upper }
+        Self::rounded(choices[0], choices[3])
     }
 }
 
@@ -178,49 +230,36 @@ impl Div for Interval {
         let Interval { low: a, upper: b } = self;
         let Interval { low: c, upper: d } = rhs;
 
-        if Int::Num(1) <= c {
-            Interval {
-                low: min(a / c, a / d),
-                upper: max(b / c, b / d),
-            }
-        } else if d <= Int::Num(-1) {
-            Interval {
-                low: min(b / c, b / d),
-                upper: max(a / c, a / d),
-            }
-        } else {
-            self / (rhs.glb(&Interval {
+        if Int::Num(0) <= c {
+            // 0 is not a possible divisor
+            let Interval { low: c, upper: d } = rhs.glb(&Interval {
                 low: Int::Num(1),
                 upper: Int::PosInf,
-            }))
+            });
+            Self::rounded(min(a / c, a / d), max(b / c, b / d))
+        } else if d <= Int::Num(0) {
+            // x / [c,d] = -(x / [-d,-c])
+            let Interval { low, upper } = self / Interval { low: -d, upper: -c };
+            Self::rounded(-upper, -low)
+        } else {
+            (self
+                / rhs.glb(&Interval {
+                    low: Int::Num(1),
+                    upper: Int::PosInf,
+                }))
             .lub(
                 &(self
-                    / (rhs.glb(&Interval {
+                    / rhs.glb(&Interval {
                         low: Int::NegInf,
                         upper: Int::Num(-1),
-                    }))),
+                    })),
             )
         }
     }
 }
 
 impl AbstractDomain for Interval {
-    fn init() {
-        let mut m_lock = M.write().unwrap();
-        let m = match env::var("M") {
-            Ok(value) => Int::try_from(value.as_str()).unwrap_or(Int::NegInf),
-            Err(_) => Int::NegInf,
-        };
-        *m_lock = m;
-
-        let mut n_lock = N.write().unwrap();
-        let n = match env::var("N") {
-            Ok(value) => Int::try_from(value.as_str()).unwrap_or(Int::PosInf),
-            Err(_) => Int::PosInf,
-        };
-        println!("Interval domain bounds: M: {:#?}, N: {:#?}", m, n);
-        *n_lock = n;
-    }
+    type Context = Bounds;
 
     fn bottom() -> Self {
         BOTTOM
@@ -235,12 +274,44 @@ impl AbstractDomain for Interval {
         }
     }
     fn lub(&self, other: &Self) -> Self {
+        // empty intervals may have any bounds, such as the results of glb
+        if *self == BOTTOM {
+            return *other;
+        }
+        if *other == BOTTOM {
+            return *self;
+        }
         Interval {
             low: min(self.low, other.low),
             upper: max(self.upper, other.upper),
         }
     }
 
+    // |x % y| is smaller than |y| and at most |x|, with the sign of x
+    fn rem(self, rhs: Self) -> Self {
+        if self == BOTTOM || rhs == BOTTOM || rhs == ZERO {
+            return BOTTOM;
+        }
+        if let (Int::Num(a), Int::Num(b)) = (self.low, rhs.low)
+            && self.low == self.upper
+            && rhs.low == rhs.upper
+        {
+            return Self::constant_abstraction(a.wrapping_rem(b));
+        }
+
+        let Interval { low: a, upper: b } = self;
+        let (smallest, largest) = rhs.divisor_magnitudes();
+        // every dividend is smaller than every divisor in absolute value
+        if -smallest < a && b < smallest {
+            return self;
+        }
+        let bound = largest - Int::Num(1);
+        Interval {
+            low: min(max(a, -bound), Int::Num(0)),
+            upper: max(min(b, bound), Int::Num(0)),
+        }
+    }
+
     fn constant_abstraction(c: i64) -> Self {
         Interval {
             low: Int::Num(c),
@@ -264,16 +335,16 @@ impl AbstractDomain for Interval {
         Interval { low, upper }
     }
 
-    fn widening_operator() -> Option<impl Fn(&Self, &Self, &HashSet<i64>) -> Self> {
-        let m = *M.read().unwrap();
-        let n = *N.read().unwrap();
-
-        if m > n || m != Int::NegInf && n != Int::PosInf {
+    // with finite bounds the domain has finite height
+    fn widening_operator(
+        ctx: &Self::Context,
+    ) -> Option<impl Fn(&Self, &Self, &HashSet<i64>) -> Self> {
+        if ctx.m != Int::NegInf && ctx.n != Int::PosInf {
             return None;
         }
 
         fn widening(lhs: &Interval, rhs: &Interval, thresholds: &HashSet<i64>) -> Interval {
-            let thresholds: Vec<Int> = thresholds.into_iter().map(|t| Int::Num(*t)).collect();
+            let thresholds: Vec<Int> = thresholds.iter().map(|t| Int::Num(*t)).collect();
             let low = match lhs.low <= rhs.low {
                 true => lhs.low,
                 _ => {
@@ -304,6 +375,23 @@ impl AbstractDomain for Interval {
         Some(widening)
     }
 
+    fn normalize(self, ctx: &Self::Context) -> Self {
+        if self.low > self.upper {
+            return BOTTOM;
+        }
+        let low = match self.low {
+            low if low < ctx.m => Int::NegInf,
+            low if low > ctx.n => ctx.n,
+            low => low,
+        };
+        let upper = match self.upper {
+            upper if upper > ctx.n => Int::PosInf,
+            upper if upper < ctx.m => ctx.m,
+            upper => upper,
+        };
+        Interval { low, upper }
+    }
+
     fn narrowing(&self, rhs: &Self) -> Self {
         let Interval { low: a, upper: b } = *self;
         let Interval { low: c, upper: d } = *rhs;
@@ -318,6 +406,44 @@ impl AbstractDomain for Interval {
 
         Interval { low, upper }
     }
+
+    fn backward_arithmetic_operator(
+        lhs: Self,
+        rhs: Self,
+        result: Self,
+        operator: Operator,
+    ) -> [Self; 2] {
+        if operator != Operator::Mod {
+            return default_backward_arithmetic_operator(lhs, rhs, result, operator);
+        }
+        if result == BOTTOM {
+            return [BOTTOM; 2];
+        }
+        let zero = Int::Num(0);
+        let (smallest, _) = rhs.divisor_magnitudes();
+        // x % y = r implies |x| >= |r| with the sign of r, and x = r when |x| < |y|
+        let lhs_ref = match (result.low, result.upper) {
+            (r, _) if r > zero => lhs.glb(&Interval::new(r, Int::PosInf)),
+            (_, r) if r < zero => lhs.glb(&Interval::new(Int::NegInf, r)),
+            _ => lhs,
+        };
+        let lhs_ref = match -smallest < lhs_ref.low && lhs_ref.upper < smallest {
+            true => lhs_ref.glb(&result),
+            false => lhs_ref,
+        };
+        // |y| > |r|
+        let min_result = match (result.low, result.upper) {
+            (r, _) if r > zero => r,
+            (_, r) if r < zero => -r,
+            _ => zero,
+        };
+        let rhs_ref = match (rhs.low, rhs.upper) {
+            (c, _) if c >= zero => rhs.glb(&Interval::new(min_result + Int::Num(1), Int::PosInf)),
+            (_, d) if d <= zero => rhs.glb(&Interval::new(Int::NegInf, -min_result - Int::Num(1))),
+            _ => rhs,
+        };
+        [lhs_ref, rhs_ref]
+    }
 }
 
 #[derive(Debug, Clone, Copy)]
@@ -350,30 +476,9 @@ impl<'a> TryFrom<&'a str> for Interval {
     }
 }
 
-impl<'a> Into<String> for Interval {
-    fn into(self) -> String {
-        let m = *M.read().unwrap();
-        let n = *N.read().unwrap();
-
-        let mut low = self.low;
-        let mut upper = self.upper;
-        if self == TOP {
-            low = Int::NegInf;
-            upper = Int::PosInf;
-        } else if m <= n {
-            if low < m {
-                low = Int::NegInf
-            } else if low > n {
-                low = n;
-            }
-            if upper < m {
-                upper = n
-            } else if upper > n {
-                upper = Int::PosInf
-            }
-        }
-
-        format! {"[{},{}]", Into::<String>::into(low), Into::<String>::into(upper)}
+impl From<Interval> for String {
+    fn from(value: Interval) -> Self {
+        format! {"[{},{}]", Into::<String>::into(value.low), Into::<String>::into(value.upper)}
     }
 }
 
@@ -381,20 +486,16 @@ impl<'a> Into<String> for Interval {
 mod test {
     use std::ops::{Add, Div, Mul};
 
-    use crate::abstract_domains::{
-        int::Int,
-        interval::{BOTTOM, TOP, ZERO},
+    use crate::{
+        abstract_domains::{
+            abstract_domain::AbstractDomain,
+            int::Int,
+            interval::{BOTTOM, TOP, ZERO},
+        },
+        parser::ast::Operator,
     };
 
-    use super::{Interval, M, N};
-
-    fn set_domain_bounds(m: Int, n: Int) {
-        let mut m_lock = M.write().unwrap();
-        *m_lock = m;
-
-        let mut n_lock = N.write().unwrap();
-        *n_lock = n
-    }
+    use super::{Bounds, Interval};
 
     fn singleton(v: i64) -> Interval {
         Interval {
@@ -403,16 +504,10 @@ mod test {
         }
     }
 
-    fn constant_domain() {
-        set_domain_bounds(Int::PosInf, Int::NegInf);
-    }
-
-    fn interval_domain() {
-        set_domain_bounds(Int::NegInf, Int::PosInf);
-    }
-
-    fn restricted_domain(low: i64, upper: i64) {
-        set_domain_bounds(Int::Num(low), Int::Num(upper));
+    // the interval in the parametric domain with bounds [m, n]
+    fn restricted(m: i64, n: i64, intv: impl Into<Interval>) -> Interval {
+        intv.into()
+            .normalize(&Bounds::new(Int::Num(m), Int::Num(n)))
     }
 
     fn minus_inf_to(x: i64) -> Interval {
@@ -431,72 +526,69 @@ mod test {
 
     #[test]
     fn intv_abs_domain_cmp() {
-        constant_domain();
         assert!(BOTTOM <= BOTTOM);
         assert!(TOP <= TOP);
         assert!(singleton(1) <= singleton(1));
-        assert_eq!(singleton(1) <= singleton(2), false);
+        assert!(!singleton(1).le(&singleton(2)));
 
-        restricted_domain(-5, 5);
-        assert!(minus_inf_to(0) <= [-6, 0].into());
-        assert!(TOP <= [-6, 6].into());
-        assert_eq!(
-            <[i64; 2] as Into<Interval>>::into([1, 4]) <= [3, 5].into(),
-            false
-        );
+        assert!(minus_inf_to(0) <= restricted(-5, 5, [-6, 0]));
+        assert!(TOP <= restricted(-5, 5, [-6, 6]));
+        assert!(!restricted(-5, 5, [1, 4]).le(&restricted(-5, 5, [3, 5])));
     }
 
     #[test]
     fn intv_abs_domain_eq() {
-        constant_domain();
         assert_eq!(BOTTOM, BOTTOM);
         assert_eq!(singleton(1), singleton(1));
         assert_ne!(singleton(1), singleton(2));
-        assert_eq!(TOP, [0, 1].into());
+        assert_eq!(Interval::from([3, 2]), BOTTOM);
+        assert_eq!(Interval::from([0, -1]).lub(&[1, 0].into()), BOTTOM);
 
-        restricted_domain(-5, 5);
-        assert!(Interval::eq(&[-3, 2].into(), &[-3, 2].into()));
-        assert_eq!(minus_inf_to(0), [-6, 0].into());
-        assert_eq!(TOP, [-6, 6].into());
+        assert!(Interval::eq(
+            &restricted(-5, 5, [-3, 2]),
+            &restricted(-5, 5, [-3, 2])
+        ));
+        assert_eq!(minus_inf_to(0), restricted(-5, 5, [-6, 0]));
+        assert_eq!(TOP, restricted(-5, 5, [-6, 6]));
+        assert_eq!(x_to_inf(5), restricted(-5, 5, [7, 8]));
+        assert_eq!(minus_inf_to(-5), restricted(-5, 5, [-8, -7]));
     }
 
     #[test]
     fn intv_abs_domain_add() {
-        constant_domain();
         assert_eq!(BOTTOM + singleton(1), BOTTOM);
         assert_eq!(TOP + singleton(1), TOP);
         assert_eq!(TOP + BOTTOM, BOTTOM);
         assert_eq!(singleton(1) + singleton(2), singleton(3));
 
-        restricted_domain(-5, 5);
         assert_eq!(
-            Interval::add([-3, 0].into(), [-2, 5].into()),
+            restricted(-5, 5, Interval::add([-3, 0].into(), [-2, 5].into())),
             [-5, 5].into()
         );
-        assert!(singleton(-1) + [-5, 5].into() <= [-6, 4].into());
-        assert!(singleton(5) + singleton(1) <= [5, 6].into());
+        assert!(restricted(-5, 5, singleton(-1) + [-5, 5].into()) <= restricted(-5, 5, [-6, 4]));
+        assert!(restricted(-5, 5, singleton(5) + singleton(1)) <= restricted(-5, 5, [5, 6]));
 
-        interval_domain();
         assert_eq!(x_to_inf(0) + [-200, -10].into(), x_to_inf(-200))
     }
 
     #[test]
     fn intv_abs_domain_sub() {
-        constant_domain();
         assert_eq!(BOTTOM - TOP, BOTTOM);
         assert_eq!(TOP - TOP, TOP);
         assert_eq!(singleton(0) - singleton(10), singleton(-10));
 
-        restricted_domain(-5, 5);
-        assert_eq!(singleton(5) - [0, 5].into(), [0, 5].into());
-        assert_eq!(singleton(-5) - [0, 1].into(), [-6, -5].into());
-        assert!(singleton(-5) - singleton(1) <= [-6, -5].into());
-        assert!(singleton(-5) - singleton(1) <= [-6, -5].into());
+        assert_eq!(
+            restricted(-5, 5, singleton(5) - [0, 5].into()),
+            [0, 5].into()
+        );
+        assert_eq!(
+            restricted(-5, 5, singleton(-5) - [0, 1].into()),
+            restricted(-5, 5, [-6, -5])
+        );
+        assert!(restricted(-5, 5, singleton(-5) - singleton(1)) <= restricted(-5, 5, [-6, -5]));
 
-        restricted_domain(0, 5);
-        assert_eq!(singleton(5) - singleton(0), singleton(5));
+        assert_eq!(restricted(0, 5, singleton(5) - singleton(0)), singleton(5));
 
-        interval_domain();
         assert_eq!(minus_inf_to(100) - singleton(-10), minus_inf_to(110));
 
         assert_eq!(minus_inf_to(10) - minus_inf_to(-1), TOP);
@@ -504,20 +596,33 @@ mod test {
 
     #[test]
     fn intv_abs_domain_mul() {
-        constant_domain();
         assert_eq!(ZERO * TOP, ZERO);
         assert_eq!(ZERO * BOTTOM, BOTTOM);
         assert_eq!(singleton(5) * singleton(2), singleton(10));
 
-        restricted_domain(-5, 5);
-        assert_eq!(singleton(5) * singleton(2), x_to_inf(5));
-        assert_eq!(Interval::mul([0, 2].into(), [0, 3].into()), x_to_inf(0));
-        assert_eq!(singleton(10) * [-1, 1].into(), TOP)
+        assert_eq!(restricted(-5, 5, singleton(5) * singleton(2)), x_to_inf(5));
+        assert_eq!(
+            restricted(-5, 5, Interval::mul([0, 2].into(), [0, 3].into())),
+            x_to_inf(0)
+        );
+        assert_eq!(restricted(-5, 5, singleton(10) * [-1, 1].into()), TOP)
+    }
+
+    #[test]
+    fn intv_abs_domain_overflow() {
+        // the bounds beyond the i64 are infinite, never on their wrong side
+        assert_eq!(singleton(i64::MAX) + singleton(1), x_to_inf(i64::MAX));
+        assert_eq!(x_to_inf(1) - singleton(i64::MIN), x_to_inf(i64::MAX));
+        assert_eq!(singleton(i64::MIN) - singleton(1), minus_inf_to(i64::MIN));
+        assert_eq!(
+            Interval::mul([2, 3].into(), [i64::MAX - 1, i64::MAX].into()),
+            x_to_inf(i64::MAX)
+        );
+        assert_eq!(singleton(i64::MIN) / singleton(-1), x_to_inf(i64::MAX));
     }
 
     #[test]
     fn intv_abs_domain_div() {
-        constant_domain();
         // assert_eq!(BOTTOM / TOP, BOTTOM);
         //[0,0]/[-inf, inf] = [0,0]/[-inf,0] U [0,0]/[0,inf] = [0,0]/[0,inf] U [0,0]/[0,inf] = [min(0/0,0/inf), max(0/0,0/inf)] = [0,0]
         assert_eq!(ZERO / x_to_inf(0), ZERO);
@@ -526,23 +631,86 @@ mod test {
         assert_eq!(singleton(1) / singleton(2), ZERO);
         assert_eq!(singleton(1) / singleton(1), singleton(1));
 
-        restricted_domain(-5, 5);
         assert_eq!(
-            //[1,1] / [-3,0] = [-1,-1]/[0,3] = [-inf, 0]
-            singleton(1) / [0, 3].into(),
-            x_to_inf(0)
+            //[1,1] / [0,3] = [1,1]/[1,3] = [0, 1]
+            restricted(-5, 5, singleton(1) / [0, 3].into()),
+            [0, 1].into()
         );
         assert_eq!(
-            //[-3,-1]/[-3,0] = [1,3]/[0,3] = [0, inf]
-            Interval::div([-3, -1].into(), [-3, 0].into()),
-            x_to_inf(0)
+            //[-3,-1]/[-3,0] = -([-3,-1]/[1,3]) = [0, 3]
+            restricted(-5, 5, Interval::div([-3, -1].into(), [-3, 0].into())),
+            [0, 3].into()
         );
         //[-5,-1] / [0,2] = [-inf, inf]
         //assert_eq!(Interval::from("[-5,1]") / "[0,2]".into(), TOP);
 
-        interval_domain();
-        // C >= 0 => [min X, max X] => [0, inf]
-        // X = (a/c,a/d,b/c,b/d) = (10/0, 10/inf) = (inf, 0)
-        assert_eq!(singleton(10) / x_to_inf(0), x_to_inf(0))
+        // C >= 0 => [min X, max X] of C without 0 => [0, 10]
+        // X = (a/c,a/d,b/c,b/d) = (10/1, 10/inf) = (10, 0)
+        assert_eq!(singleton(10) / x_to_inf(0), [0, 10].into());
+        assert_eq!(Interval::div([1, 5].into(), [0, 3].into()), [0, 5].into())
+    }
+
+    #[test]
+    fn intv_abs_domain_rem() {
+        assert_eq!(singleton(7).rem(singleton(3)), singleton(1));
+        assert_eq!(singleton(-7).rem(singleton(3)), singleton(-1));
+        assert_eq!(TOP.rem(ZERO), BOTTOM);
+        // the result has the sign of the dividend and is smaller than the divisor
+        assert_eq!(x_to_inf(0).rem(singleton(4)), [0, 3].into());
+        assert_eq!(Interval::from([-10, 2]).rem([-4, 3].into()), [-3, 2].into());
+        assert_eq!(minus_inf_to(-1).rem(x_to_inf(0)), minus_inf_to(0));
+        // dividends smaller than the divisor are left unchanged
+        assert_eq!(Interval::from([1, 3]).rem([5, 8].into()), [1, 3].into());
+    }
+
+    #[test]
+    fn intv_abs_domain_backward_rem() {
+        // x % y in [2,3] => x >= 2 and y not in [-2,2]
+        assert_eq!(
+            Interval::backward_arithmetic_operator(TOP, x_to_inf(0), [2, 3].into(), Operator::Mod),
+            [x_to_inf(2), x_to_inf(3)]
+        );
+        // x % 5 = -1 with x in [-4,4] => x = -1
+        assert_eq!(
+            Interval::backward_arithmetic_operator(
+                [-4, 4].into(),
+                singleton(5),
+                singleton(-1),
+                Operator::Mod
+            ),
+            [singleton(-1), singleton(5)]
+        );
+        // x % y = 0 excludes a zero divisor
+        assert_eq!(
+            Interval::backward_arithmetic_operator(TOP, [0, 3].into(), ZERO, Operator::Mod),
+            [TOP, [1, 3].into()]
+        );
+    }
+
+    #[test]
+    fn intv_abs_domain_widening_bounds() {
+        let bounds = Bounds::default();
+        let unbounded = Interval::widening_operator(&bounds).unwrap();
+        assert_eq!(
+            unbounded(&[0, 1].into(), &[0, 2].into(), &[10].into()),
+            [0, 10].into()
+        );
+        // the parametric domain with finite bounds has finite height
+        let bounds = Bounds::new(Int::Num(-5), Int::Num(5));
+        assert!(Interval::widening_operator(&bounds).is_none());
+    }
+
+    #[test]
+    fn intv_abs_domain_div_zero_bound() {
+        // 0 is not a possible divisor, the divisors [0,d] and [c,0] are [1,d] and [c,-1]
+        assert_eq!(Interval::div([4, 8].into(), [0, 2].into()), [2, 8].into());
+        assert_eq!(
+            Interval::div([4, 8].into(), [-2, 0].into()),
+            [-8, -2].into()
+        );
+        assert_eq!(
+            Interval::div([-8, -4].into(), [-2, 0].into()),
+            [2, 8].into()
+        );
     }
 }
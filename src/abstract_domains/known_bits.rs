@@ -0,0 +1,625 @@
+use core::fmt;
+use std::{
+    cmp::Ordering,
+    collections::HashSet,
+    ops::{Add, Div, Mul, Sub},
+};
+
+use crate::parser::ast::Operator;
+
+use super::abstract_domain::{AbstractDomain, IntervalBound, default_backward_arithmetic_operator};
+
+// bits of the two's complement representation of an i64 that are definitely 0 (zeros) or
+// definitely 1 (ones), the bits are computed modulo 2^64 but the integers do not wrap around, so
+// an operation that may leave the i64 gives top
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub struct KnownBits {
+    zeros: u64,
+    ones: u64,
+}
+
+const SIGN: u64 = 1 << 63;
+
+impl KnownBits {
+    const TOP: KnownBits = KnownBits { zeros: 0, ones: 0 };
+    // every bit is both 0 and 1
+    const BOTTOM: KnownBits = KnownBits {
+        zeros: u64::MAX,
+        ones: u64::MAX,
+    };
+
+    fn new(zeros: u64, ones: u64) -> Self {
+        match zeros & ones {
+            0 => KnownBits { zeros, ones },
+            _ => Self::BOTTOM,
+        }
+    }
+
+    fn is_bottom(self) -> bool {
+        self.zeros & self.ones != 0
+    }
+
+    fn known(self) -> u64 {
+        self.zeros | self.ones
+    }
+
+    fn as_constant(self) -> Option<i64> {
+        match !self.is_bottom() && self.known() == u64::MAX {
+            true => Some(self.ones as i64),
+            false => None,
+        }
+    }
+
+    fn not(self) -> Self {
+        KnownBits {
+            zeros: self.ones,
+            ones: self.zeros,
+        }
+    }
+
+    fn shl(self, s: u32) -> Self {
+        match s {
+            0 => self,
+            64.. => Self::constant_abstraction(0),
+            _ => KnownBits {
+                zeros: (self.zeros << s) | ((1 << s) - 1),
+                ones: self.ones << s,
+            },
+        }
+    }
+
+    // logical shift, the vacated high bits are 0
+    fn lshr(self, s: u32) -> Self {
+        match s {
+            0 => self,
+            64.. => Self::constant_abstraction(0),
+            _ => KnownBits {
+                zeros: (self.zeros >> s) | !(u64::MAX >> s),
+                ones: self.ones >> s,
+            },
+        }
+    }
+
+    // forgets the bits outside the mask
+    fn keep(self, mask: u64) -> Self {
+        KnownBits {
+            zeros: self.zeros & mask,
+            ones: self.ones & mask,
+        }
+    }
+
+    // smallest and largest i64 with these bits
+    fn range(self) -> (i64, i64) {
+        let unknown = !self.known();
+        (
+            (self.ones | (unknown & SIGN)) as i64,
+            (self.ones | (unknown & !SIGN)) as i64,
+        )
+    }
+
+    // range of the operation on the ranges of the operands, None when it may leave the i64; the
+    // operation is monotone in each operand so the extremes are reached on the bounds
+    fn range_of(lhs: Self, rhs: Self, op: impl Fn(i128, i128) -> i128) -> Option<(i64, i64)> {
+        let ((a, b), (c, d)) = (lhs.range(), rhs.range());
+        let results = [(a, c), (a, d), (b, c), (b, d)]
+            .iter()
+            .map(|(x, y)| i64::try_from(op(i128::from(*x), i128::from(*y))).ok())
+            .collect::<Option<Vec<_>>>()?;
+        Some((*results.iter().min()?, *results.iter().max()?))
+    }
+
+    // lhs + rhs + carry modulo 2^64, a bit of the result is known when the bits of both operands
+    // and the incoming carry are known
+    fn add_carry(lhs: Self, rhs: Self, carry: bool) -> Self {
+        if lhs.is_bottom() || rhs.is_bottom() {
+            return Self::BOTTOM;
+        }
+        let max_sum = (!lhs.zeros)
+            .wrapping_add(!rhs.zeros)
+            .wrapping_add(carry as u64);
+        let min_sum = lhs.ones.wrapping_add(rhs.ones).wrapping_add(carry as u64);
+
+        let carry_zeros = !(max_sum ^ lhs.zeros ^ rhs.zeros);
+        let carry_ones = min_sum ^ lhs.ones ^ rhs.ones;
+        let known = lhs.known() & rhs.known() & (carry_zeros | carry_ones);
+        KnownBits {
+            zeros: !max_sum & known,
+            ones: min_sum & known,
+        }
+    }
+
+    // sum of the shifted copies of lhs modulo 2^64, one for each bit set in c
+    fn mul_constant(self, c: i64) -> Self {
+        let m = c.unsigned_abs();
+        let product = (0..64)
+            .filter(|i| m & (1 << i) != 0)
+            .fold(Self::constant_abstraction(0), |acc, i| {
+                Self::add_carry(acc, self.shl(i), false)
+            });
+        match c < 0 {
+            true => Self::add_carry(Self::constant_abstraction(0), product.not(), true),
+            false => product,
+        }
+    }
+
+    // refines x knowing that x * c = result
+    fn refine_factor(x: Self, c: i64, result: Self) -> Self {
+        if c == 0 {
+            return match result.zeros == u64::MAX {
+                true => x,
+                false => Self::BOTTOM,
+            };
+        }
+        // c = odd * 2^s, the low s bits of the result are 0 and x * odd = result >> s on the
+        // 64 - s low bits, where odd is invertible
+        let s = c.trailing_zeros();
+        if result.ones & ((1 << s) - 1) != 0 {
+            return Self::BOTTOM;
+        }
+        let odd = c >> s;
+        let inverse = (0..6).fold(odd, |y, _| {
+            y.wrapping_mul(2_i64.wrapping_sub(odd.wrapping_mul(y)))
+        });
+        let low = result.lshr(s).mul_constant(inverse).keep(u64::MAX >> s);
+        x.glb(&low)
+    }
+}
+
+impl PartialOrd for KnownBits {
+    fn partial_cmp(&self, other: &Self) -> Option<Ordering> {
+        let le = |lhs: &Self, rhs: &Self| {
+            lhs.is_bottom() || (rhs.zeros & !lhs.zeros == 0 && rhs.ones & !lhs.ones == 0)
+        };
+        match (le(self, other), le(other, self)) {
+            (true, true) => Some(Ordering::Equal),
+            (true, false) => Some(Ordering::Less),
+            (false, true) => Some(Ordering::Greater),
+            _ => None,
+        }
+    }
+}
+
+impl Add for KnownBits {
+    type Output = Self;
+    fn add(self, rhs: Self) -> Self::Output {
+        if self.is_bottom() || rhs.is_bottom() {
+            return Self::BOTTOM;
+        }
+        match Self::range_of(self, rhs, |x, y| x + y) {
+            Some(_) => Self::add_carry(self, rhs, false),
+            None => Self::TOP,
+        }
+    }
+}
+
+// lhs - rhs = lhs + !rhs + 1
+impl Sub for KnownBits {
+    type Output = Self;
+    fn sub(self, rhs: Self) -> Self::Output {
+        if self.is_bottom() || rhs.is_bottom() {
+            return Self::BOTTOM;
+        }
+        match Self::range_of(self, rhs, |x, y| x - y) {
+            Some(_) => Self::add_carry(self, rhs.not(), true),
+            None => Self::TOP,
+        }
+    }
+}
+
+impl Mul for KnownBits {
+    type Output = Self;
+    fn mul(self, rhs: Self) -> Self::Output {
+        if self.is_bottom() || rhs.is_bottom() {
+            return Self::BOTTOM;
+        }
+        let Some((min, max)) = Self::range_of(self, rhs, |x, y| x * y) else {
+            return Self::TOP;
+        };
+        match (self.as_constant(), rhs.as_constant()) {
+            (_, Some(c)) => return self.mul_constant(c),
+            (Some(c), _) => return rhs.mul_constant(c),
+            _ => (),
+        }
+
+        // the low bits of a product only depend on the low bits of the operands
+        let low = self
+            .known()
+            .trailing_ones()
+            .min(rhs.known().trailing_ones());
+        let low_mask = match low {
+            64 => u64::MAX,
+            _ => (1 << low) - 1,
+        };
+        let low_product = self.ones.wrapping_mul(rhs.ones);
+        let trailing_zeros = self.zeros.trailing_ones() + rhs.zeros.trailing_ones();
+        let zeros_mask = match trailing_zeros {
+            64.. => u64::MAX,
+            _ => (1 << trailing_zeros) - 1,
+        };
+        let product = KnownBits {
+            zeros: (!low_product & low_mask) | zeros_mask,
+            ones: low_product & low_mask & !zeros_mask,
+        };
+        // the high bits are the ones shared by the range of the product
+        product.glb(&Self::interval_abstraction(
+            IntervalBound::Num(min),
+            IntervalBound::Num(max),
+        ))
+    }
+}
+
+// truncated division, division by zero has no result
+impl Div for KnownBits {
+    type Output = Self;
+    fn div(self, rhs: Self) -> Self::Output {
+        if self.is_bottom() || rhs.is_bottom() || rhs.ones == 0 && rhs.zeros == u64::MAX {
+            return Self::BOTTOM;
+        }
+        match (self.as_constant(), rhs.as_constant()) {
+            (Some(a), Some(b)) => {
+                return a
+                    .checked_div(b)
+                    .map_or(Self::TOP, Self::constant_abstraction);
+            }
+            (_, Some(1)) => return self,
+            (_, Some(-1)) => return Self::constant_abstraction(0) - self,
+            (_, Some(b)) if b > 0 && b.count_ones() == 1 && self.zeros & SIGN != 0 => {
+                return self.lshr(b.trailing_zeros());
+            }
+            _ => (),
+        }
+
+        // between non negative numbers the quotient is at most the dividend
+        match self.zeros & rhs.zeros & SIGN {
+            0 => Self::TOP,
+            _ => {
+                let leading_zeros = self.zeros.leading_ones();
+                KnownBits {
+                    zeros: !(u64::MAX >> leading_zeros),
+                    ones: 0,
+                }
+            }
+        }
+    }
+}
+
+impl AbstractDomain for KnownBits {
+    type Context = ();
+
+    fn top() -> Self {
+        Self::TOP
+    }
+
+    fn bottom() -> Self {
+        Self::BOTTOM
+    }
+
+    fn lub(&self, other: &Self) -> Self {
+        match (self.is_bottom(), other.is_bottom()) {
+            (true, _) => *other,
+            (_, true) => *self,
+            _ => KnownBits {
+                zeros: self.zeros & other.zeros,
+                ones: self.ones & other.ones,
+            },
+        }
+    }
+
+    fn glb(&self, other: &Self) -> Self {
+        Self::new(self.zeros | other.zeros, self.ones | other.ones)
+    }
+
+    // x % y = x - y * q keeps the bits of x below the trailing zeros of y, the other ones are
+    // known only for a non negative x modulo a power of two
+    fn rem(self, rhs: Self) -> Self {
+        if self.is_bottom() || rhs.is_bottom() || rhs.ones == 0 && rhs.zeros == u64::MAX {
+            return Self::BOTTOM;
+        }
+        if let (Some(a), Some(b)) = (self.as_constant(), rhs.as_constant()) {
+            return Self::constant_abstraction(a.wrapping_rem(b));
+        }
+        let low_mask = (1 << rhs.zeros.trailing_ones()) - 1;
+        let low = self.keep(low_mask);
+        match (self.zeros & SIGN != 0, rhs.as_constant()) {
+            (true, Some(b)) if b.unsigned_abs().count_ones() == 1 => KnownBits {
+                zeros: low.zeros | !low_mask,
+                ones: low.ones,
+            },
+            (true, _) => KnownBits {
+                zeros: low.zeros | SIGN,
+                ones: low.ones,
+            },
+            _ => low,
+        }
+    }
+
+    fn constant_abstraction(c: i64) -> Self {
+        KnownBits {
+            zeros: !(c as u64),
+            ones: c as u64,
+        }
+    }
+
+    // the bits above the highest one where the bounds differ are shared by the whole range
+    // when the bounds have the same sign
+    fn interval_abstraction(low: IntervalBound, upper: IntervalBound) -> Self {
+        let low = match low {
+            IntervalBound::NegInf => i64::MIN,
+            IntervalBound::Num(x) => x,
+            _ => panic!("PosInf found while parsing a concrete interval to an abstract domain"),
+        };
+        let upper = match upper {
+            IntervalBound::PosInf => i64::MAX,
+            IntervalBound::Num(x) => x,
+            _ => panic!("NegInf found while parsing a concrete interval to an abstract domain"),
+        };
+
+        if low > upper {
+            return Self::BOTTOM;
+        }
+        let differing = (low as u64) ^ (upper as u64);
+        let common = match differing {
+            0 => u64::MAX,
+            _ => !(u64::MAX >> differing.leading_zeros()),
+        };
+        Self::constant_abstraction(low).keep(common)
+    }
+
+    // every ascending chain changes at most 128 bits, plain lub iterations already terminate
+    fn widening_operator(
+        _ctx: &Self::Context,
+    ) -> Option<impl Fn(&Self, &Self, &HashSet<i64>) -> Self> {
+        None::<fn(&Self, &Self, &HashSet<i64>) -> Self>
+    }
+
+    // addition and subtraction are invertible modulo 2^64, so is the multiplication by an odd
+    // constant, and an i64 is determined by its value modulo 2^64
+    fn backward_arithmetic_operator(
+        lhs: Self,
+        rhs: Self,
+        result: Self,
+        operator: Operator,
+    ) -> [Self; 2] {
+        let wrapping_sub = |lhs: Self, rhs: Self| Self::add_carry(lhs, rhs.not(), true);
+        match operator {
+            Operator::Add => [
+                lhs.glb(&wrapping_sub(result, rhs)),
+                rhs.glb(&wrapping_sub(result, lhs)),
+            ],
+            Operator::Sub => [
+                lhs.glb(&Self::add_carry(result, rhs, false)),
+                rhs.glb(&wrapping_sub(lhs, result)),
+            ],
+            Operator::Mul => match (lhs.as_constant(), rhs.as_constant()) {
+                (_, Some(c)) => [Self::refine_factor(lhs, c, result), rhs],
+                (Some(c), _) => [lhs, Self::refine_factor(rhs, c, result)],
+                _ => [lhs, rhs],
+            },
+            Operator::Div => [lhs, rhs],
+            _ => default_backward_arithmetic_operator(lhs, rhs, result, operator),
+        }
+    }
+}
+
+#[derive(Debug, Clone, Copy)]
+pub struct BadKnownBits<'a>(&'a str);
+
+impl<'a> fmt::Display for BadKnownBits<'a> {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        write!(f, "invalid conversion {} -> KnownBits", self.0)
+    }
+}
+
+// "0b" followed by the bits from the most significant one, using ? for the unknown ones and c{n}
+// for n copies of c, the first bit is repeated up to the sign bit: 0b?00 is a multiple of 4,
+// 0b1? is -1 or -2 and 0b0?{63} is non negative
+impl<'a> TryFrom<&'a str> for KnownBits {
+    type Error = BadKnownBits<'a>;
+    fn try_from(value: &'a str) -> Result<Self, Self::Error> {
+        let trimmed = value.trim();
+        match trimmed {
+            "⊥" | "bottom" => return Ok(Self::BOTTOM),
+            "⊤" | "top" => return Ok(Self::TOP),
+            _ => (),
+        }
+        if let Ok(c) = trimmed.parse::<i64>() {
+            return Ok(Self::constant_abstraction(c));
+        }
+
+        let pattern = trimmed.strip_prefix("0b").ok_or(BadKnownBits(value))?;
+        let mut bits = vec![];
+        let mut chars = pattern.chars().peekable();
+        while let Some(bit) = chars.next() {
+            if !matches!(bit, '0' | '1' | '?') {
+                return Err(BadKnownBits(value));
+            }
+            let copies = match chars.next_if_eq(&'{') {
+                Some(_) => {
+                    let n: String = chars.by_ref().take_while(|c| *c != '}').collect();
+                    n.parse().map_err(|_| BadKnownBits(value))?
+                }
+                None => 1,
+            };
+            bits.extend(std::iter::repeat_n(bit, copies));
+        }
+        if bits.is_empty() || bits.len() > 64 {
+            return Err(BadKnownBits(value));
+        }
+
+        let extended = std::iter::repeat_n(bits[0], 64 - bits.len()).chain(bits);
+        let mut r = Self::TOP;
+        for (i, bit) in extended.enumerate() {
+            match bit {
+                '0' => r.zeros |= SIGN >> i,
+                '1' => r.ones |= SIGN >> i,
+                _ => (),
+            }
+        }
+        Ok(r)
+    }
+}
+
+impl From<KnownBits> for String {
+    fn from(value: KnownBits) -> Self {
+        if value.is_bottom() {
+            return "⊥".to_string();
+        }
+        if value == KnownBits::TOP {
+            return "⊤".to_string();
+        }
+        if let Some(c) = value.as_constant() {
+            return c.to_string();
+        }
+
+        let bits: Vec<char> = (0..64)
+            .map(|i| SIGN >> i)
+            .map(|mask| match (value.zeros & mask, value.ones & mask) {
+                (0, 0) => '?',
+                (0, _) => '1',
+                _ => '0',
+            })
+            .collect();
+        // the leading copies of the sign bit are implicit
+        let start = (0..63).find(|i| bits[*i] != bits[i + 1]).unwrap_or(63);
+        let mut pattern = String::from("0b");
+        for run in bits[start..].chunk_by(|a, b| a == b) {
+            match run.len() {
+                1..=4 => pattern.extend(run),
+                n => pattern.push_str(&format!("{}{{{n}}}", run[0])),
+            }
+        }
+        pattern
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use crate::{
+        abstract_domains::abstract_domain::{AbstractDomain, IntervalBound},
+        parser::ast::Operator,
+    };
+
+    use super::KnownBits;
+
+    fn bits(value: &str) -> KnownBits {
+        KnownBits::try_from(value).unwrap()
+    }
+
+    fn constant(c: i64) -> KnownBits {
+        KnownBits::constant_abstraction(c)
+    }
+
+    #[test]
+    fn known_bits_abs_domain_lattice() {
+        assert_eq!(constant(4).lub(&constant(6)), bits("0b01?0"));
+        assert_eq!(bits("0b?00").glb(&bits("0b?1??")), bits("0b?100"));
+        assert_eq!(constant(1).glb(&constant(2)), KnownBits::bottom());
+        assert!(constant(4) <= bits("0b?00"));
+        assert!(!bits("0b?0").le(&bits("0b?00")));
+        assert_eq!(constant(-1).lub(&constant(-2)), bits("0b1?"));
+    }
+
+    #[test]
+    fn known_bits_abs_domain_add_sub() {
+        assert_eq!(constant(3) + constant(5), constant(8));
+        assert_eq!(constant(3) - constant(5), constant(-2));
+        // multiples of 4 plus 1 are odd
+        assert_eq!(bits("0b?00") + constant(1), bits("0b?01"));
+        assert_eq!(bits("0b0?{16}00") + bits("0b0?{16}00"), bits("0b0?{17}00"));
+        assert_eq!(bits("0b?01") - constant(1), bits("0b?00"));
+    }
+
+    #[test]
+    fn known_bits_abs_domain_overflow() {
+        let top = KnownBits::top();
+        assert_eq!(constant(i64::MAX) + constant(1), top);
+        assert_eq!(constant(i64::MIN) - constant(1), top);
+        assert_eq!(constant(i64::MIN) / constant(-1), top);
+        assert_eq!(constant(i64::MIN) * constant(-1), top);
+        assert_eq!(bits("0b0?{63}") * constant(2), top);
+        // non negative numbers below 2^32 can be added and multiplied
+        assert_eq!(bits("0b0?{32}") + bits("0b0?{32}"), bits("0b0?{33}"));
+        assert_eq!(bits("0b0?{31}0") * bits("0b0?{31}"), bits("0b0?{62}0"));
+        // the backward addition is exact modulo 2^64
+        assert_eq!(
+            KnownBits::backward_arithmetic_operator(
+                top,
+                constant(1),
+                constant(i64::MIN),
+                Operator::Add
+            )[0],
+            constant(i64::MAX)
+        );
+    }
+
+    #[test]
+    fn known_bits_abs_domain_mul() {
+        let x = bits("0b0?{16}");
+        assert_eq!(x * constant(4), bits("0b0?{16}00"));
+        assert_eq!(bits("0b0?{16}0") * bits("0b0?{16}0"), bits("0b0?{32}00"));
+        assert_eq!(bits("0b0?{16}01") * bits("0b0?{16}11"), bits("0b0?{34}11"));
+        assert_eq!(constant(6) * constant(-7), constant(-42));
+        assert_eq!(bits("0b01?") * constant(2), bits("0b01?0"));
+    }
+
+    #[test]
+    fn known_bits_abs_domain_div() {
+        assert_eq!(constant(42) / constant(-5), constant(-8));
+        assert_eq!(bits("0b01?00") / constant(4), bits("0b01?"));
+        assert_eq!(bits("0b0001??") / bits("0b0?"), bits("0b0???"));
+        assert_eq!(KnownBits::top() / constant(2), KnownBits::top());
+        assert_eq!(KnownBits::top() / constant(0), KnownBits::bottom());
+    }
+
+    #[test]
+    fn known_bits_abs_domain_abstraction() {
+        assert_eq!(
+            KnownBits::interval_abstraction(IntervalBound::Num(8), IntervalBound::Num(11)),
+            bits("0b010??")
+        );
+        assert_eq!(
+            KnownBits::interval_abstraction(IntervalBound::NegInf, IntervalBound::Num(-1)),
+            bits("0b1?{63}")
+        );
+        assert_eq!(
+            KnownBits::interval_abstraction(IntervalBound::Num(-1), IntervalBound::Num(1)),
+            KnownBits::top()
+        );
+    }
+
+    #[test]
+    fn known_bits_abs_domain_backward() {
+        let x = KnownBits::top();
+        // x * 3 = 9 => x = 3
+        assert_eq!(
+            KnownBits::backward_arithmetic_operator(x, constant(3), constant(9), Operator::Mul),
+            [constant(3), constant(3)]
+        );
+        // x * 4 is never odd
+        assert_eq!(
+            KnownBits::backward_arithmetic_operator(x, constant(4), bits("0b?1"), Operator::Mul)[0],
+            KnownBits::bottom()
+        );
+        // x * 4 = 8 => x is 2 modulo 2^62
+        assert_eq!(
+            KnownBits::backward_arithmetic_operator(constant(4), x, constant(8), Operator::Mul)[1],
+            bits("0b??0{60}10")
+        );
+        // x + 1 = 8 => x = 7
+        assert_eq!(
+            KnownBits::backward_arithmetic_operator(x, constant(1), constant(8), Operator::Add),
+            [constant(7), constant(1)]
+        );
+    }
+
+    #[test]
+    fn known_bits_abs_domain_parse() {
+        assert_eq!(String::from(bits("0b?00")), "0b?00");
+        assert_eq!(String::from(bits("0b0001?")), "0b01?");
+        assert_eq!(String::from(constant(-3)), "-3");
+        assert_eq!(String::from(bits("0b0?{63}")), "0b0?{63}");
+        assert_eq!(bits("0b1?{3}0"), bits("0b1???0"));
+        assert_eq!(bits("12"), constant(12));
+        assert!(KnownBits::try_from("0b12").is_err());
+    }
+}
@@ -1,3 +1,8 @@
 pub mod abstract_domain;
+pub mod congruence;
+pub mod constant;
 pub mod int;
 pub mod interval;
+pub mod known_bits;
+pub mod product;
+pub mod sign;
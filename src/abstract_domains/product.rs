@@ -0,0 +1,407 @@
+use core::fmt;
+use std::{
+    cmp::Ordering,
+    collections::HashSet,
+    marker::PhantomData,
+    ops::{Add, Div, Mul, Sub},
+};
+
+use crate::parser::ast::Operator;
+
+use super::{
+    abstract_domain::{AbstractDomain, IntervalBound},
+    congruence::Congruence,
+    int::Int,
+    interval::Interval,
+};
+
+// reduction step applied after every operation of the product, it may only exchange
+// information between the components, never lose it
+pub trait Reduction<D1, D2> {
+    fn reduce(lhs: D1, rhs: D2) -> (D1, D2);
+}
+
+#[derive(Clone, Copy, Debug, PartialEq)]
+pub struct NoReduction;
+
+impl<D1: AbstractDomain, D2: AbstractDomain> Reduction<D1, D2> for NoReduction {
+    fn reduce(lhs: D1, rhs: D2) -> (D1, D2) {
+        (lhs, rhs)
+    }
+}
+
+// moves the interval bounds onto the closest values of the congruence class: [1,10] ∧ 2Z = [2,10] ∧ 2Z
+#[derive(Clone, Copy, Debug, PartialEq)]
+pub struct IntervalCongruence;
+
+// (to - from) mod modulus, the difference itself may not fit in an i64
+fn distance(from: i64, to: i64, modulus: i64) -> i64 {
+    (i128::from(to) - i128::from(from)).rem_euclid(i128::from(modulus)) as i64
+}
+
+impl Reduction<Interval, Congruence> for IntervalCongruence {
+    fn reduce(lhs: Interval, rhs: Congruence) -> (Interval, Congruence) {
+        let Congruence::Mod { modulus, residue } = rhs else {
+            return (Interval::bottom(), Congruence::bottom());
+        };
+        if modulus == 0 {
+            let intv = lhs.glb(&Interval::constant_abstraction(residue));
+            return match intv == Interval::bottom() {
+                true => (Interval::bottom(), Congruence::bottom()),
+                false => (intv, rhs),
+            };
+        }
+
+        let low = match lhs.low() {
+            Int::Num(low) => low
+                .checked_add(distance(low, residue, modulus))
+                .map_or(lhs.low(), Int::Num),
+            bound => bound,
+        };
+        let upper = match lhs.upper() {
+            Int::Num(upper) => upper
+                .checked_sub(distance(residue, upper, modulus))
+                .map_or(lhs.upper(), Int::Num),
+            bound => bound,
+        };
+
+        match (low, upper) {
+            (low, upper) if low > upper => (Interval::bottom(), Congruence::bottom()),
+            (Int::Num(low), Int::Num(upper)) if low == upper => (
+                Interval::constant_abstraction(low),
+                Congruence::constant_abstraction(low),
+            ),
+            _ => (Interval::new(low, upper), rhs),
+        }
+    }
+}
+
+#[derive(Clone, Copy, Debug, PartialEq)]
+pub struct Product<D1, D2, R = NoReduction> {
+    lhs: D1,
+    rhs: D2,
+    reduction: PhantomData<R>,
+}
+
+impl<D1, D2, R> Product<D1, D2, R>
+where
+    D1: AbstractDomain,
+    D2: AbstractDomain,
+    R: Reduction<D1, D2>,
+{
+    pub fn new(lhs: D1, rhs: D2) -> Self {
+        let (lhs, rhs) = match lhs == D1::bottom() || rhs == D2::bottom() {
+            true => (D1::bottom(), D2::bottom()),
+            false => R::reduce(lhs, rhs),
+        };
+        Product {
+            lhs,
+            rhs,
+            reduction: PhantomData,
+        }
+    }
+
+    fn map(self, rhs: Self, f1: fn(D1, D1) -> D1, f2: fn(D2, D2) -> D2) -> Self {
+        Product::new(f1(self.lhs, rhs.lhs), f2(self.rhs, rhs.rhs))
+    }
+}
+
+impl<D1, D2, R> PartialOrd for Product<D1, D2, R>
+where
+    D1: AbstractDomain,
+    D2: AbstractDomain,
+    R: Reduction<D1, D2> + PartialEq,
+{
+    fn partial_cmp(&self, other: &Self) -> Option<Ordering> {
+        use Ordering::*;
+        match (
+            self.lhs.partial_cmp(&other.lhs)?,
+            self.rhs.partial_cmp(&other.rhs)?,
+        ) {
+            (Equal, Equal) => Some(Equal),
+            (Less | Equal, Less | Equal) => Some(Less),
+            (Greater | Equal, Greater | Equal) => Some(Greater),
+            _ => None,
+        }
+    }
+}
+
+impl<D1, D2, R> Add for Product<D1, D2, R>
+where
+    D1: AbstractDomain,
+    D2: AbstractDomain,
+    R: Reduction<D1, D2>,
+{
+    type Output = Self;
+    fn add(self, rhs: Self) -> Self::Output {
+        self.map(rhs, D1::add, D2::add)
+    }
+}
+
+impl<D1, D2, R> Sub for Product<D1, D2, R>
+where
+    D1: AbstractDomain,
+    D2: AbstractDomain,
+    R: Reduction<D1, D2>,
+{
+    type Output = Self;
+    fn sub(self, rhs: Self) -> Self::Output {
+        self.map(rhs, D1::sub, D2::sub)
+    }
+}
+
+impl<D1, D2, R> Mul for Product<D1, D2, R>
+where
+    D1: AbstractDomain,
+    D2: AbstractDomain,
+    R: Reduction<D1, D2>,
+{
+    type Output = Self;
+    fn mul(self, rhs: Self) -> Self::Output {
+        self.map(rhs, D1::mul, D2::mul)
+    }
+}
+
+impl<D1, D2, R> Div for Product<D1, D2, R>
+where
+    D1: AbstractDomain,
+    D2: AbstractDomain,
+    R: Reduction<D1, D2>,
+{
+    type Output = Self;
+    fn div(self, rhs: Self) -> Self::Output {
+        self.map(rhs, D1::div, D2::div)
+    }
+}
+
+impl<D1, D2, R> AbstractDomain for Product<D1, D2, R>
+where
+    D1: AbstractDomain,
+    D2: AbstractDomain,
+    R: Reduction<D1, D2> + Clone + Copy + fmt::Debug + PartialEq,
+{
+    type Context = (D1::Context, D2::Context);
+
+    fn top() -> Self {
+        Product::new(D1::top(), D2::top())
+    }
+
+    fn bottom() -> Self {
+        Product::new(D1::bottom(), D2::bottom())
+    }
+
+    fn lub(&self, other: &Self) -> Self {
+        Product::new(self.lhs.lub(&other.lhs), self.rhs.lub(&other.rhs))
+    }
+
+    fn glb(&self, other: &Self) -> Self {
+        Product::new(self.lhs.glb(&other.lhs), self.rhs.glb(&other.rhs))
+    }
+
+    fn rem(self, rhs: Self) -> Self {
+        self.map(rhs, D1::rem, D2::rem)
+    }
+
+    fn constant_abstraction(c: i64) -> Self {
+        Product::new(D1::constant_abstraction(c), D2::constant_abstraction(c))
+    }
+
+    fn interval_abstraction(low: IntervalBound, upper: IntervalBound) -> Self {
+        Product::new(
+            D1::interval_abstraction(low, upper),
+            D2::interval_abstraction(low, upper),
+        )
+    }
+
+    // a component without widening has finite height and falls back to lub. The result is not
+    // reduced, reducing after a widening may break the termination of the iterations
+    fn widening_operator(
+        ctx: &Self::Context,
+    ) -> Option<impl Fn(&Self, &Self, &HashSet<i64>) -> Self> {
+        let lhs_widening = D1::widening_operator(&ctx.0);
+        let rhs_widening = D2::widening_operator(&ctx.1);
+        if lhs_widening.is_none() && rhs_widening.is_none() {
+            return None;
+        }
+
+        Some(move |lhs: &Self, rhs: &Self, thresholds: &HashSet<i64>| {
+            if lhs.lhs == D1::bottom() {
+                return *rhs;
+            }
+            if rhs.lhs == D1::bottom() {
+                return *lhs;
+            }
+            Product {
+                lhs: lhs_widening.as_ref().map_or_else(
+                    || lhs.lhs.lub(&rhs.lhs),
+                    |w| w(&lhs.lhs, &rhs.lhs, thresholds),
+                ),
+                rhs: rhs_widening.as_ref().map_or_else(
+                    || lhs.rhs.lub(&rhs.rhs),
+                    |w| w(&lhs.rhs, &rhs.rhs, thresholds),
+                ),
+                reduction: PhantomData,
+            }
+        })
+    }
+
+    fn normalize(self, ctx: &Self::Context) -> Self {
+        Product::new(self.lhs.normalize(&ctx.0), self.rhs.normalize(&ctx.1))
+    }
+
+    fn narrowing(&self, rhs: &Self) -> Self {
+        Product::new(self.lhs.narrowing(&rhs.lhs), self.rhs.narrowing(&rhs.rhs))
+    }
+
+    fn backward_arithmetic_operator(
+        lhs: Self,
+        rhs: Self,
+        result: Self,
+        operator: Operator,
+    ) -> [Self; 2] {
+        let [l1, r1] = D1::backward_arithmetic_operator(lhs.lhs, rhs.lhs, result.lhs, operator);
+        let [l2, r2] = D2::backward_arithmetic_operator(lhs.rhs, rhs.rhs, result.rhs, operator);
+        [Product::new(l1, l2), Product::new(r1, r2)]
+    }
+}
+
+#[derive(Debug, Clone, Copy)]
+pub struct BadProduct<'a>(&'a str);
+
+impl<'a> fmt::Display for BadProduct<'a> {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        write!(f, "invalid conversion {} -> Product", self.0)
+    }
+}
+
+// accepts "(d1, d2)", or a single component leaving the other one to top
+impl<'a, D1, D2, R> TryFrom<&'a str> for Product<D1, D2, R>
+where
+    D1: AbstractDomain,
+    D2: AbstractDomain,
+    R: Reduction<D1, D2>,
+{
+    type Error = BadProduct<'a>;
+    fn try_from(value: &'a str) -> Result<Self, Self::Error> {
+        let trimmed = value.trim();
+        if let Some(inner) = trimmed.strip_prefix('(').and_then(|s| s.strip_suffix(')')) {
+            // components may contain commas themselves, e.g. intervals
+            let pair = inner.match_indices(',').find_map(|(i, _)| {
+                let lhs = D1::try_from(&inner[..i]).ok()?;
+                let rhs = D2::try_from(&inner[i + 1..]).ok()?;
+                Some(Product::new(lhs, rhs))
+            });
+            if let Some(pair) = pair {
+                return Ok(pair);
+            }
+        }
+
+        if let Ok(lhs) = D1::try_from(trimmed) {
+            return Ok(Product::new(lhs, D2::top()));
+        }
+        if let Ok(rhs) = D2::try_from(trimmed) {
+            return Ok(Product::new(D1::top(), rhs));
+        }
+        Err(BadProduct(value))
+    }
+}
+
+impl<D1, D2, R> From<Product<D1, D2, R>> for String
+where
+    D1: AbstractDomain,
+    D2: AbstractDomain,
+{
+    fn from(value: Product<D1, D2, R>) -> Self {
+        format!(
+            "({}, {})",
+            Into::<String>::into(value.lhs),
+            Into::<String>::into(value.rhs)
+        )
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use crate::{
+        abstract_domains::{
+            abstract_domain::AbstractDomain, congruence::Congruence, interval::Interval, sign::Sign,
+        },
+        parser::ast::Operator,
+    };
+
+    use super::{IntervalCongruence, Product};
+
+    type IntvCong = Product<Interval, Congruence, IntervalCongruence>;
+
+    fn product(value: &str) -> IntvCong {
+        IntvCong::try_from(value).unwrap()
+    }
+
+    #[test]
+    fn product_reduction() {
+        assert_eq!(product("([1,10], 2Z)"), product("([2,10], 2Z)"));
+        assert_eq!(product("([1,10], 4Z+3)"), product("([3,7], 4Z+3)"));
+        assert_eq!(product("([1,3], 4Z)"), IntvCong::bottom());
+        assert_eq!(product("([5,7], 4Z+2)"), IntvCong::constant_abstraction(6));
+        assert_eq!(product("([-inf,0], 3Z+1)"), product("([-inf,-2], 3Z+1)"));
+    }
+
+    #[test]
+    fn product_reduction_bounds() {
+        let multiples = Congruence::try_from("3Z").unwrap();
+        let all = IntvCong::new([i64::MIN, i64::MAX].into(), multiples);
+        let reduced = IntvCong::new([i64::MIN + 2, i64::MAX - 1].into(), multiples);
+        assert_eq!(all, reduced);
+    }
+
+    #[test]
+    fn product_arithmetic() {
+        let even = product("([0,10], 2Z)");
+        assert_eq!(
+            even + IntvCong::constant_abstraction(1),
+            product("([1,11], 2Z+1)")
+        );
+        assert_eq!(
+            even * IntvCong::constant_abstraction(3),
+            product("([0,30], 6Z)")
+        );
+        assert_eq!(even.lub(&product("([1,1], 1)")), product("([0,10], Z)"));
+        assert_eq!(even.glb(&product("([3,7], Z)")), product("([4,6], 2Z)"));
+    }
+
+    #[test]
+    fn product_without_reduction() {
+        type SignCong = Product<Sign, Congruence>;
+        let p = SignCong::try_from("(>0, 2Z)").unwrap();
+        assert_eq!(
+            p + SignCong::constant_abstraction(1),
+            SignCong::try_from("(>0, 2Z+1)").unwrap()
+        );
+        assert_eq!(String::from(p), "(>0, 2Z)");
+        assert_eq!(
+            SignCong::try_from("<0").unwrap(),
+            SignCong::try_from("(<0, Z)").unwrap()
+        );
+    }
+
+    #[test]
+    fn product_backward() {
+        // x + 1 in [0,10] ∧ 2Z => x in [-1,9] ∧ 2Z+1 => [-1,9] ∧ 2Z+1
+        let [lhs, _] = IntvCong::backward_arithmetic_operator(
+            IntvCong::top(),
+            IntvCong::constant_abstraction(1),
+            product("([0,10], 2Z)"),
+            Operator::Add,
+        );
+        assert_eq!(lhs, product("([-1,9], 2Z+1)"));
+
+        // x % 2 = 0 with x in [1,10] => x in [2,10] ∧ 2Z
+        let [lhs, _] = IntvCong::backward_arithmetic_operator(
+            product("([1,10], 1Z)"),
+            IntvCong::constant_abstraction(2),
+            IntvCong::constant_abstraction(0),
+            Operator::Mod,
+        );
+        assert_eq!(lhs, product("([2,10], 2Z)"));
+    }
+}
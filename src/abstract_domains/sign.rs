@@ -0,0 +1,408 @@
+use core::fmt;
+use std::{
+    cmp::Ordering,
+    collections::HashSet,
+    ops::{Add, Div, Mul, Sub},
+};
+
+use crate::parser::ast::Operator;
+
+use super::abstract_domain::{AbstractDomain, IntervalBound};
+
+// atoms of the sign lattice, every element is a set of them
+const NEG: u8 = 0b001;
+const ZERO: u8 = 0b010;
+const POS: u8 = 0b100;
+const ATOMS: [u8; 3] = [NEG, ZERO, POS];
+
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub enum Sign {
+    Bottom,
+    Neg,
+    Zero,
+    Pos,
+    NonPos,
+    NonNeg,
+    NonZero,
+    Top,
+}
+
+impl Sign {
+    fn atoms(self) -> u8 {
+        match self {
+            Sign::Bottom => 0,
+            Sign::Neg => NEG,
+            Sign::Zero => ZERO,
+            Sign::Pos => POS,
+            Sign::NonPos => NEG | ZERO,
+            Sign::NonNeg => ZERO | POS,
+            Sign::NonZero => NEG | POS,
+            Sign::Top => NEG | ZERO | POS,
+        }
+    }
+
+    fn from_atoms(atoms: u8) -> Self {
+        match atoms {
+            0 => Sign::Bottom,
+            NEG => Sign::Neg,
+            ZERO => Sign::Zero,
+            POS => Sign::Pos,
+            0b011 => Sign::NonPos,
+            0b110 => Sign::NonNeg,
+            0b101 => Sign::NonZero,
+            _ => Sign::Top,
+        }
+    }
+
+    // lifts an operator defined on single atoms to the whole lattice
+    fn lift(self, rhs: Self, op: fn(u8, u8) -> u8) -> Self {
+        let mut r = 0;
+        for a in ATOMS.iter().filter(|a| self.atoms() & **a != 0) {
+            for b in ATOMS.iter().filter(|b| rhs.atoms() & **b != 0) {
+                r |= op(*a, *b);
+            }
+        }
+        Sign::from_atoms(r)
+    }
+}
+
+fn atom_add(lhs: u8, rhs: u8) -> u8 {
+    match (lhs, rhs) {
+        (ZERO, x) | (x, ZERO) => x,
+        (x, y) if x == y => x,
+        _ => NEG | ZERO | POS,
+    }
+}
+
+fn atom_neg(x: u8) -> u8 {
+    match x {
+        NEG => POS,
+        POS => NEG,
+        _ => x,
+    }
+}
+
+fn atom_sub(lhs: u8, rhs: u8) -> u8 {
+    atom_add(lhs, atom_neg(rhs))
+}
+
+fn atom_mul(lhs: u8, rhs: u8) -> u8 {
+    match (lhs, rhs) {
+        (ZERO, _) | (_, ZERO) => ZERO,
+        (x, y) if x == y => POS,
+        _ => NEG,
+    }
+}
+
+// truncated division: |lhs| < |rhs| yields 0
+fn atom_div(lhs: u8, rhs: u8) -> u8 {
+    match (lhs, rhs) {
+        (_, ZERO) => 0,
+        (ZERO, _) => ZERO,
+        (x, y) if x == y => ZERO | POS,
+        _ => NEG | ZERO,
+    }
+}
+
+// truncated remainder: the sign of lhs, or 0
+fn atom_rem(lhs: u8, rhs: u8) -> u8 {
+    match (lhs, rhs) {
+        (_, ZERO) => 0,
+        (ZERO, _) => ZERO,
+        (x, _) => x | ZERO,
+    }
+}
+
+impl PartialOrd for Sign {
+    fn partial_cmp(&self, other: &Self) -> Option<Ordering> {
+        let (lhs, rhs) = (self.atoms(), other.atoms());
+        match lhs | rhs {
+            _ if lhs == rhs => Some(Ordering::Equal),
+            x if x == rhs => Some(Ordering::Less),
+            x if x == lhs => Some(Ordering::Greater),
+            _ => None,
+        }
+    }
+}
+
+impl Add for Sign {
+    type Output = Self;
+    fn add(self, rhs: Self) -> Self::Output {
+        self.lift(rhs, atom_add)
+    }
+}
+
+impl Sub for Sign {
+    type Output = Self;
+    fn sub(self, rhs: Self) -> Self::Output {
+        self.lift(rhs, atom_sub)
+    }
+}
+
+impl Mul for Sign {
+    type Output = Self;
+    fn mul(self, rhs: Self) -> Self::Output {
+        self.lift(rhs, atom_mul)
+    }
+}
+
+impl Div for Sign {
+    type Output = Self;
+    fn div(self, rhs: Self) -> Self::Output {
+        self.lift(rhs, atom_div)
+    }
+}
+
+impl AbstractDomain for Sign {
+    type Context = ();
+
+    fn top() -> Self {
+        Sign::Top
+    }
+
+    fn bottom() -> Self {
+        Sign::Bottom
+    }
+
+    fn lub(&self, other: &Self) -> Self {
+        Sign::from_atoms(self.atoms() | other.atoms())
+    }
+
+    fn glb(&self, other: &Self) -> Self {
+        Sign::from_atoms(self.atoms() & other.atoms())
+    }
+
+    fn rem(self, rhs: Self) -> Self {
+        self.lift(rhs, atom_rem)
+    }
+
+    fn constant_abstraction(c: i64) -> Self {
+        match c.signum() {
+            -1 => Sign::Neg,
+            0 => Sign::Zero,
+            _ => Sign::Pos,
+        }
+    }
+
+    fn interval_abstraction(low: IntervalBound, upper: IntervalBound) -> Self {
+        let low = match low {
+            IntervalBound::NegInf => i64::MIN,
+            IntervalBound::Num(x) => x,
+            _ => panic!("PosInf found while parsing a concrete interval to an abstract domain"),
+        };
+        let upper = match upper {
+            IntervalBound::PosInf => i64::MAX,
+            IntervalBound::Num(x) => x,
+            _ => panic!("NegInf found while parsing a concrete interval to an abstract domain"),
+        };
+
+        if low > upper {
+            return Sign::Bottom;
+        }
+
+        let mut atoms = 0;
+        if low < 0 {
+            atoms |= NEG;
+        }
+        if low <= 0 && 0 <= upper {
+            atoms |= ZERO;
+        }
+        if upper > 0 {
+            atoms |= POS;
+        }
+        Sign::from_atoms(atoms)
+    }
+
+    // the lattice has finite height, plain lub iterations already terminate
+    fn widening_operator(
+        _ctx: &Self::Context,
+    ) -> Option<impl Fn(&Self, &Self, &HashSet<i64>) -> Self> {
+        None::<fn(&Self, &Self, &HashSet<i64>) -> Self>
+    }
+
+    // keeps only the atoms of each operand that can produce some atom of the result
+    fn backward_arithmetic_operator(
+        lhs: Self,
+        rhs: Self,
+        result: Self,
+        operator: Operator,
+    ) -> [Self; 2] {
+        let op = match operator {
+            Operator::Add => atom_add,
+            Operator::Sub => atom_sub,
+            Operator::Mul => atom_mul,
+            Operator::Div => atom_div,
+            Operator::Mod => atom_rem,
+        };
+
+        let mut lhs_ref = 0;
+        let mut rhs_ref = 0;
+        for a in ATOMS.iter().filter(|a| lhs.atoms() & **a != 0) {
+            for b in ATOMS.iter().filter(|b| rhs.atoms() & **b != 0) {
+                if op(*a, *b) & result.atoms() != 0 {
+                    lhs_ref |= a;
+                    rhs_ref |= b;
+                }
+            }
+        }
+        [Sign::from_atoms(lhs_ref), Sign::from_atoms(rhs_ref)]
+    }
+}
+
+#[derive(Debug, Clone, Copy)]
+pub struct BadSign<'a>(&'a str);
+
+impl<'a> fmt::Display for BadSign<'a> {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        write!(f, "invalid conversion {} -> Sign", self.0)
+    }
+}
+
+impl<'a> TryFrom<&'a str> for Sign {
+    type Error = BadSign<'a>;
+    fn try_from(value: &'a str) -> Result<Self, Self::Error> {
+        match value.trim() {
+            "⊥" | "bottom" => Ok(Sign::Bottom),
+            "<0" => Ok(Sign::Neg),
+            "=0" | "0" => Ok(Sign::Zero),
+            ">0" => Ok(Sign::Pos),
+            "<=0" | "≤0" => Ok(Sign::NonPos),
+            ">=0" | "≥0" => Ok(Sign::NonNeg),
+            "!=0" | "≠0" => Ok(Sign::NonZero),
+            "⊤" | "top" => Ok(Sign::Top),
+            _ => Err(BadSign(value)),
+        }
+    }
+}
+
+impl From<Sign> for String {
+    fn from(value: Sign) -> Self {
+        match value {
+            Sign::Bottom => "⊥",
+            Sign::Neg => "<0",
+            Sign::Zero => "=0",
+            Sign::Pos => ">0",
+            Sign::NonPos => "<=0",
+            Sign::NonNeg => ">=0",
+            Sign::NonZero => "!=0",
+            Sign::Top => "⊤",
+        }
+        .to_string()
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use crate::{
+        abstract_domains::abstract_domain::{AbstractDomain, IntervalBound},
+        parser::ast::Operator,
+    };
+
+    use super::Sign::{self, *};
+
+    #[test]
+    fn sign_abs_domain_cmp() {
+        assert!(Bottom <= Neg);
+        assert!(Neg <= NonPos);
+        assert!(Zero <= NonNeg);
+        assert!(NonZero <= Top);
+        assert!(Top >= Pos);
+        assert!(!Neg.le(&Pos));
+        assert!(!NonPos.le(&NonNeg));
+        assert_eq!(NonPos.partial_cmp(&NonNeg), None);
+    }
+
+    #[test]
+    fn sign_abs_domain_lattice() {
+        assert_eq!(Neg.lub(&Zero), NonPos);
+        assert_eq!(Neg.lub(&Pos), NonZero);
+        assert_eq!(NonPos.lub(&Pos), Top);
+        assert_eq!(NonPos.glb(&NonNeg), Zero);
+        assert_eq!(NonZero.glb(&NonNeg), Pos);
+        assert_eq!(Neg.glb(&Pos), Bottom);
+    }
+
+    #[test]
+    fn sign_abs_domain_add() {
+        assert_eq!(Bottom + Pos, Bottom);
+        assert_eq!(Pos + Pos, Pos);
+        assert_eq!(Pos + Zero, Pos);
+        assert_eq!(Pos + NonNeg, Pos);
+        assert_eq!(Neg + NonPos, Neg);
+        assert_eq!(Neg + Pos, Top);
+    }
+
+    #[test]
+    fn sign_abs_domain_sub() {
+        assert_eq!(Pos - Neg, Pos);
+        assert_eq!(Zero - Pos, Neg);
+        assert_eq!(NonNeg - NonPos, NonNeg);
+        assert_eq!(Pos - Pos, Top);
+    }
+
+    #[test]
+    fn sign_abs_domain_mul() {
+        assert_eq!(Neg * Neg, Pos);
+        assert_eq!(Neg * Pos, Neg);
+        assert_eq!(Zero * Top, Zero);
+        assert_eq!(NonZero * NonZero, NonZero);
+        assert_eq!(NonNeg * Neg, NonPos);
+    }
+
+    #[test]
+    fn sign_abs_domain_div() {
+        assert_eq!(Top / Zero, Bottom);
+        assert_eq!(Pos / Pos, NonNeg);
+        assert_eq!(Neg / Pos, NonPos);
+        assert_eq!(Zero / NonZero, Zero);
+        assert_eq!(Pos / Top, Top);
+    }
+
+    #[test]
+    fn sign_abs_domain_abstraction() {
+        assert_eq!(Sign::constant_abstraction(-3), Neg);
+        assert_eq!(
+            Sign::interval_abstraction(IntervalBound::Num(0), IntervalBound::PosInf),
+            NonNeg
+        );
+        assert_eq!(
+            Sign::interval_abstraction(IntervalBound::NegInf, IntervalBound::Num(-1)),
+            Neg
+        );
+        assert_eq!(
+            Sign::interval_abstraction(IntervalBound::Num(-1), IntervalBound::Num(1)),
+            Top
+        );
+    }
+
+    #[test]
+    fn sign_abs_domain_backward() {
+        // x * y < 0 with y > 0 => x < 0
+        assert_eq!(
+            Sign::backward_arithmetic_operator(Top, Pos, Neg, Operator::Mul),
+            [Neg, Pos]
+        );
+        // x * 0 = 0 does not constrain x
+        assert_eq!(
+            Sign::backward_arithmetic_operator(Top, Zero, Zero, Operator::Mul),
+            [Top, Zero]
+        );
+        // x - y > 0 with x <= 0 => y < 0
+        assert_eq!(
+            Sign::backward_arithmetic_operator(NonPos, Top, Pos, Operator::Sub),
+            [NonPos, Neg]
+        );
+        assert_eq!(
+            Sign::backward_arithmetic_operator(Pos, Pos, Neg, Operator::Add),
+            [Bottom, Bottom]
+        );
+    }
+
+    #[test]
+    fn sign_abs_domain_parse() {
+        assert_eq!(Sign::try_from(" >=0 ").unwrap(), NonNeg);
+        assert_eq!(Sign::try_from("!=0").unwrap(), NonZero);
+        assert!(Sign::try_from("[0,1]").is_err());
+        assert_eq!(String::from(NonPos), "<=0");
+    }
+}
@@ -0,0 +1,78 @@
+use std::{
+    collections::HashSet,
+    fmt::{Debug, Display},
+};
+
+use crate::{
+    abstract_domains::abstract_domain::{AbstractDomain, IntervalBound},
+    parser::ast::{ArithmeticCondition, ArithmeticExp, Operator, Position},
+};
+
+use super::partitioned::Choice;
+
+// abstraction of the whole memory, the interpreter is generic over it so that relational
+// domains can track constraints between variables
+pub trait AbstractState<'a>: Clone + PartialEq + Debug + Display + Sized {
+    // abstraction of the value of a single variable
+    type Value: AbstractDomain;
+
+    // configuration of the analysis, see AbstractDomain::Context
+    type Context: Clone + Debug + Default;
+
+    fn build(vars: HashSet<&'a str>, ctx: &Self::Context) -> Self;
+    fn bottom() -> Self;
+    fn is_bottom(&self) -> bool;
+    fn lub(&self, other: &Self) -> Self;
+    fn glb(&self, other: &Self) -> Self;
+    // when the domain has no widening this is expected to return rhs
+    fn widening(&self, rhs: &Self, thresholds: &HashSet<i64>) -> Self;
+    fn narrowing(&self, rhs: &Self) -> Self;
+    fn value_of(&self, var: &str) -> Self::Value;
+    fn assign(&self, var: &'a str, value: &ArithmeticExp<'a>) -> Self;
+    fn filter(&self, cond: &ArithmeticCondition<'a>) -> Self;
+    fn vars(&self) -> HashSet<&'a str>;
+
+    // trace partitioning hooks: tags the traces with the choice they made at pos, or forgets all
+    // the tags, states that do not keep traces apart ignore them
+    fn partition(&self, _pos: &Position, _choice: Choice) -> Self {
+        self.clone()
+    }
+    fn merge_partitions(&self) -> Self {
+        self.clone()
+    }
+
+    fn eval(&self, exp: &ArithmeticExp<'a>) -> Self::Value {
+        match exp {
+            ArithmeticExp::Variable(var, _) => self.value_of(var),
+            ArithmeticExp::Integer(x, _) => Self::Value::constant_abstraction(*x),
+            ArithmeticExp::Negation(exp, _) => {
+                Self::Value::constant_abstraction(0) - self.eval(exp)
+            }
+            ArithmeticExp::Random {
+                low,
+                upper,
+                span: _,
+            } => Self::Value::interval_abstraction(
+                IntervalBound::Num(*low),
+                IntervalBound::Num(*upper),
+            ),
+            ArithmeticExp::Unknown(_) => Self::Value::top(),
+            ArithmeticExp::BinaryOperation {
+                lhs,
+                operator,
+                rhs,
+                span: _,
+            } => {
+                let lhs_value = self.eval(lhs);
+                let rhs_value = self.eval(rhs);
+                match operator {
+                    Operator::Add => lhs_value + rhs_value,
+                    Operator::Sub => lhs_value - rhs_value,
+                    Operator::Mul => lhs_value * rhs_value,
+                    Operator::Div => lhs_value / rhs_value,
+                    Operator::Mod => lhs_value.rem(rhs_value),
+                }
+            }
+        }
+    }
+}
@@ -0,0 +1,187 @@
+use std::cmp::{max, min};
+
+use crate::{
+    abstract_domains::{
+        int::Int,
+        interval::{Bounds, Interval},
+    },
+    parser::ast::ArithmeticCondition,
+    state::State,
+};
+
+use super::abstract_state::AbstractState;
+
+// a bound that overflowed below the i64 is rounded up to the smallest one
+fn rounded(bound: Int) -> Int {
+    max(bound, Int::Num(i64::MIN))
+}
+
+// refines the interval projection of a relational state with the non-relational propagation
+// algorithm, one interval per variable or None when the condition never holds
+pub fn filter_intervals<'a>(
+    vars: &[&'a str],
+    value_of: impl Fn(&str) -> Interval,
+    cond: &ArithmeticCondition<'a>,
+) -> Option<Vec<Interval>> {
+    let intervals = State::new(
+        vars.iter().map(|var| (*var, value_of(var))).collect(),
+        Bounds::default(),
+    );
+    let refined = intervals.filter(cond);
+    (!refined.is_bottom()).then(|| vars.iter().map(|var| *refined.lookup(var)).collect())
+}
+
+// bound of v_j - v_i when not implied by the unary bounds of the two variables
+pub fn relational_bound(dbm: &Dbm, i: usize, j: usize, implied: Int) -> Option<i64> {
+    match (dbm.get(i, j), implied) {
+        (Int::Num(c), Int::Num(implied)) if c < implied => Some(c),
+        (Int::Num(c), Int::PosInf) => Some(c),
+        _ => None,
+    }
+}
+
+// each expression with its upper bound and the upper bound of its opposite
+pub fn relational_constraints(
+    bounds: impl IntoIterator<Item = (String, Option<i64>, Option<i64>)>,
+) -> Vec<String> {
+    let mut constraints = vec![];
+    for (expr, upper, opposite) in bounds {
+        let lower = opposite.map(|c| -i128::from(c));
+        match (upper, lower) {
+            (Some(u), Some(l)) if i128::from(u) == l => constraints.push(format!("{expr} = {u}")),
+            _ => {
+                if let Some(u) = upper {
+                    constraints.push(format!("{expr} <= {u}"));
+                }
+                if let Some(l) = lower {
+                    constraints.push(format!("{expr} >= {l}"));
+                }
+            }
+        }
+    }
+    constraints
+}
+
+// difference bound matrix, entry (i, j) bounds v_j - v_i, PosInf means unconstrained
+#[derive(Clone, Debug, PartialEq)]
+pub struct Dbm {
+    dim: usize,
+    m: Vec<Int>,
+}
+
+impl Dbm {
+    pub fn top(dim: usize) -> Self {
+        let mut m = vec![Int::PosInf; dim * dim];
+        (0..dim).for_each(|i| m[i * dim + i] = Int::Num(0));
+        Dbm { dim, m }
+    }
+
+    pub fn dim(&self) -> usize {
+        self.dim
+    }
+
+    pub fn get(&self, i: usize, j: usize) -> Int {
+        self.m[i * self.dim + j]
+    }
+
+    pub fn set(&mut self, i: usize, j: usize, bound: Int) {
+        self.m[i * self.dim + j] = bound;
+    }
+
+    // keeps the tightest between the current bound and the given one
+    pub fn tighten(&mut self, i: usize, j: usize, bound: Int) {
+        let current = self.get(i, j);
+        self.set(i, j, min(current, rounded(bound)));
+    }
+
+    // adds c to every bound on v_t - v_i and subtracts it from every bound on v_i - v_t,
+    // a bound that cannot be shifted is dropped
+    pub fn translate(&mut self, t: usize, c: Int) {
+        let shift = |bound: Int, by: Int| match (bound, by) {
+            (Int::Num(_), Int::Num(_)) => rounded(bound + by),
+            _ => Int::PosInf,
+        };
+        for i in 0..self.dim {
+            if i != t {
+                self.set(i, t, shift(self.get(i, t), c));
+                self.set(t, i, shift(self.get(t, i), -c));
+            }
+        }
+    }
+
+    // Floyd–Warshall, false when a negative cycle makes the constraints unsatisfiable
+    pub fn shortest_paths(&mut self) -> bool {
+        for k in 0..self.dim {
+            for i in 0..self.dim {
+                let ik = self.get(i, k);
+                if ik == Int::PosInf {
+                    continue;
+                }
+                for j in 0..self.dim {
+                    let kj = self.get(k, j);
+                    if kj != Int::PosInf {
+                        self.tighten(i, j, ik + kj);
+                    }
+                }
+            }
+        }
+        self.is_consistent()
+    }
+
+    pub fn is_consistent(&self) -> bool {
+        (0..self.dim).all(|i| self.get(i, i) >= Int::Num(0))
+    }
+
+    pub fn join(&self, other: &Self) -> Self {
+        self.zip(other, max)
+    }
+
+    pub fn meet(&self, other: &Self) -> Self {
+        self.zip(other, min)
+    }
+
+    // unstable bounds jump to the threshold chosen by the caller for that entry
+    pub fn widening(&self, rhs: &Self, threshold: impl Fn(usize, usize, Int) -> Int) -> Self {
+        let mut r = self.clone();
+        for i in 0..self.dim {
+            for j in 0..self.dim {
+                let bound = rhs.get(i, j);
+                if bound > self.get(i, j) {
+                    r.set(i, j, threshold(i, j, bound));
+                }
+            }
+        }
+        r
+    }
+
+    // only refines the bounds the widening pushed to infinity
+    pub fn narrowing(&self, rhs: &Self) -> Self {
+        self.zip(rhs, |lhs, rhs| match lhs {
+            Int::PosInf => rhs,
+            _ => lhs,
+        })
+    }
+
+    // drops every bound involving the index
+    pub fn forget(&mut self, k: usize) {
+        for i in 0..self.dim {
+            if i != k {
+                self.set(i, k, Int::PosInf);
+                self.set(k, i, Int::PosInf);
+            }
+        }
+    }
+
+    fn zip(&self, other: &Self, f: impl Fn(Int, Int) -> Int) -> Self {
+        assert_eq!(self.dim, other.dim);
+        Dbm {
+            dim: self.dim,
+            m: self
+                .m
+                .iter()
+                .zip(other.m.iter())
+                .map(|(lhs, rhs)| f(*lhs, *rhs))
+                .collect(),
+        }
+    }
+}
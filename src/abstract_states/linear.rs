@@ -0,0 +1,96 @@
+use std::collections::BTreeMap;
+
+use crate::parser::ast::{ArithmeticExp, Operator};
+
+// sum of coeffs[var] * var + constant, variables with a zero coefficient are never stored
+#[derive(Clone, Debug, PartialEq)]
+pub struct LinearExpr<'a> {
+    pub coeffs: BTreeMap<&'a str, i64>,
+    pub constant: i64,
+}
+
+impl<'a> LinearExpr<'a> {
+    pub fn constant(c: i64) -> Self {
+        LinearExpr {
+            coeffs: BTreeMap::new(),
+            constant: c,
+        }
+    }
+
+    pub fn var(var: &'a str) -> Self {
+        LinearExpr {
+            coeffs: BTreeMap::from([(var, 1)]),
+            constant: 0,
+        }
+    }
+
+    // None when the expression is not linear or its coefficients overflow
+    pub fn from_exp(exp: &ArithmeticExp<'a>) -> Option<Self> {
+        match exp {
+            ArithmeticExp::Integer(c, _) => Some(Self::constant(*c)),
+            ArithmeticExp::Variable(var, _) => Some(Self::var(var)),
+            ArithmeticExp::Negation(exp, _) => Self::from_exp(exp)?.scale(-1),
+            // inputs are left to the interval fallback
+            ArithmeticExp::Random { .. } | ArithmeticExp::Unknown(_) => None,
+            ArithmeticExp::BinaryOperation {
+                lhs,
+                operator,
+                rhs,
+                span: _,
+            } => {
+                let lhs = Self::from_exp(lhs)?;
+                let rhs = Self::from_exp(rhs)?;
+                match operator {
+                    Operator::Add => lhs.add(&rhs),
+                    Operator::Sub => lhs.add(&rhs.scale(-1)?),
+                    Operator::Mul if lhs.coeffs.is_empty() => rhs.scale(lhs.constant),
+                    Operator::Mul if rhs.coeffs.is_empty() => lhs.scale(rhs.constant),
+                    // exact only when every coefficient is a multiple of the divisor
+                    Operator::Div if rhs.coeffs.is_empty() && rhs.constant != 0 => {
+                        let c = rhs.constant;
+                        let exact =
+                            lhs.constant % c == 0 && lhs.coeffs.values().all(|a| a % c == 0);
+                        match exact {
+                            true => Some(LinearExpr {
+                                coeffs: lhs.coeffs.iter().map(|(var, a)| (*var, a / c)).collect(),
+                                constant: lhs.constant / c,
+                            }),
+                            false => None,
+                        }
+                    }
+                    _ => None,
+                }
+            }
+        }
+    }
+
+    pub fn add(&self, rhs: &Self) -> Option<Self> {
+        let mut coeffs = self.coeffs.clone();
+        for (var, a) in rhs.coeffs.iter() {
+            let sum = coeffs.get(var).unwrap_or(&0).checked_add(*a)?;
+            match sum {
+                0 => coeffs.remove(var),
+                _ => coeffs.insert(var, sum),
+            };
+        }
+        Some(LinearExpr {
+            coeffs,
+            constant: self.constant.checked_add(rhs.constant)?,
+        })
+    }
+
+    pub fn scale(&self, k: i64) -> Option<Self> {
+        if k == 0 {
+            return Some(Self::constant(0));
+        }
+        let coeffs = self
+            .coeffs
+            .iter()
+            .map(|(var, a)| a.checked_mul(k).map(|a| (*var, a)))
+            .collect::<Option<_>>()?;
+        Some(LinearExpr {
+            coeffs,
+            constant: self.constant.checked_mul(k)?,
+        })
+    }
+}
@@ -0,0 +1,10 @@
+pub mod abstract_state;
+pub mod dbm;
+pub mod linear;
+pub mod octagon;
+pub mod partitioned;
+pub mod polyhedra;
+pub mod powerset;
+#[cfg(test)]
+pub mod test_utils;
+pub mod zone;
@@ -0,0 +1,458 @@
+use core::fmt;
+use std::collections::HashSet;
+
+use crate::{
+    abstract_domains::{abstract_domain::AbstractDomain, int::Int, interval::Interval},
+    parser::ast::{ArithmeticCondition, ArithmeticExp, ConditionOperator},
+};
+
+use super::{
+    abstract_state::AbstractState,
+    dbm::{self, Dbm},
+    linear::LinearExpr,
+};
+
+// variable k is split in v_2k = x_k and v_2k+1 = -x_k, so that every constraint
+// +-x +-y <= c becomes a difference v_j - v_i <= c
+fn pos(k: usize) -> usize {
+    2 * k
+}
+
+fn neg(k: usize) -> usize {
+    2 * k + 1
+}
+
+fn bar(i: usize) -> usize {
+    i ^ 1
+}
+
+fn term(k: usize, coeff: i64) -> usize {
+    match coeff {
+        1 => pos(k),
+        _ => neg(k),
+    }
+}
+
+fn half(bound: Int) -> Int {
+    match bound {
+        Int::Num(x) => Int::Num(x.div_euclid(2)),
+        _ => bound,
+    }
+}
+
+// tight closure for integer octagons: shortest paths, tightening of the unary bounds, strengthening
+fn close(mut dbm: Dbm) -> Option<Dbm> {
+    if !dbm.shortest_paths() {
+        return None;
+    }
+
+    let dim = dbm.dim();
+    for i in 0..dim {
+        if let Int::Num(c) = dbm.get(i, bar(i)) {
+            dbm.set(i, bar(i), Int::Num(2 * c.div_euclid(2)));
+        }
+    }
+    for i in 0..dim {
+        if dbm.get(i, bar(i)) + dbm.get(bar(i), i) < Int::Num(0) {
+            return None;
+        }
+    }
+    for i in 0..dim {
+        for j in 0..dim {
+            let implied = half(dbm.get(i, bar(i)) + dbm.get(bar(j), j));
+            dbm.tighten(i, j, implied);
+        }
+    }
+
+    dbm.is_consistent().then_some(dbm)
+}
+
+// v_j - v_i <= c together with its coherent twin
+fn add_difference(dbm: &mut Dbm, i: usize, j: usize, c: i64) {
+    dbm.tighten(i, j, Int::Num(c));
+    dbm.tighten(bar(j), bar(i), Int::Num(c));
+}
+
+#[derive(Clone, Debug)]
+pub struct Octagon<'a> {
+    vars: Vec<&'a str>,
+    // None is bottom, otherwise always closed but after a widening
+    dbm: Option<Dbm>,
+}
+
+impl<'a> PartialEq for Octagon<'a> {
+    fn eq(&self, other: &Self) -> bool {
+        match (&self.dbm, &other.dbm) {
+            (None, None) => true,
+            (Some(lhs), Some(rhs)) => self.vars == other.vars && lhs == rhs,
+            _ => false,
+        }
+    }
+}
+
+impl<'a> Octagon<'a> {
+    fn index(&self, var: &str) -> usize {
+        self.vars
+            .binary_search(&var)
+            .unwrap_or_else(|_| panic!("Variable {var} not tracked by the octagon"))
+    }
+
+    fn with_dbm(&self, dbm: Option<Dbm>) -> Self {
+        Octagon {
+            vars: self.vars.clone(),
+            dbm,
+        }
+    }
+
+    // adds expr <= 0, false when it is not an octagonal constraint
+    fn add_le_zero(&self, dbm: &mut Dbm, expr: &LinearExpr) -> bool {
+        let Some(c) = expr.constant.checked_neg() else {
+            return false;
+        };
+        let terms: Vec<_> = expr
+            .coeffs
+            .iter()
+            .map(|(var, a)| (self.index(var), *a))
+            .collect();
+        match terms[..] {
+            [(x, a)] if a.abs() == 1 => match c.checked_mul(2) {
+                Some(c) => {
+                    let j = term(x, a);
+                    dbm.tighten(bar(j), j, Int::Num(c));
+                    true
+                }
+                None => false,
+            },
+            [(x, a), (y, b)] if a.abs() == 1 && b.abs() == 1 => {
+                add_difference(dbm, bar(term(y, b)), term(x, a), c);
+                true
+            }
+            _ => false,
+        }
+    }
+
+    fn add_bounds(dbm: &mut Dbm, k: usize, value: Interval) {
+        // a doubled bound that does not fit bounds nothing
+        if let Int::Num(upper) = value.upper() {
+            dbm.tighten(neg(k), pos(k), Int::Num(2) * Int::Num(upper));
+        }
+        if let Int::Num(low) = value.low() {
+            dbm.tighten(pos(k), neg(k), Int::Num(-2) * Int::Num(low));
+        }
+    }
+
+    fn forget(dbm: &mut Dbm, k: usize) {
+        dbm.forget(pos(k));
+        dbm.forget(neg(k));
+    }
+
+    // refines the octagon with the non-relational propagation algorithm on its interval projection
+    fn filter_intervals(&self, dbm: &Dbm, cond: &ArithmeticCondition<'a>) -> Option<Dbm> {
+        let refined = dbm::filter_intervals(&self.vars, |var| self.value_of(var), cond)?;
+        let mut dbm = dbm.clone();
+        refined
+            .into_iter()
+            .enumerate()
+            .for_each(|(k, value)| Self::add_bounds(&mut dbm, k, value));
+        close(dbm)
+    }
+
+    fn relational_bound(dbm: &Dbm, i: usize, j: usize) -> Option<i64> {
+        let implied = half(dbm.get(i, bar(i)) + dbm.get(bar(j), j));
+        dbm::relational_bound(dbm, i, j, implied)
+    }
+
+    fn relational_constraints(&self, dbm: &Dbm) -> Vec<String> {
+        let mut bounds = vec![];
+        for (a, x) in self.vars.iter().enumerate() {
+            for (b, y) in self.vars.iter().enumerate().skip(a + 1) {
+                bounds.push((
+                    format!("{x} - {y}"),
+                    Self::relational_bound(dbm, pos(b), pos(a)),
+                    Self::relational_bound(dbm, pos(a), pos(b)),
+                ));
+                bounds.push((
+                    format!("{x} + {y}"),
+                    Self::relational_bound(dbm, neg(b), pos(a)),
+                    Self::relational_bound(dbm, pos(b), neg(a)),
+                ));
+            }
+        }
+        dbm::relational_constraints(bounds)
+    }
+}
+
+impl<'a> fmt::Display for Octagon<'a> {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        let Some(dbm) = self.dbm.clone().and_then(close) else {
+            return write!(f, "{{ }}");
+        };
+        let closed = self.with_dbm(Some(dbm.clone()));
+
+        let _ = write!(f, "{{ ");
+        self.vars.iter().for_each(|var| {
+            let _ = write!(
+                f,
+                "{var} := {} ",
+                Into::<String>::into(closed.value_of(var))
+            );
+        });
+        let constraints = self.relational_constraints(&dbm);
+        if !constraints.is_empty() {
+            let _ = write!(f, "| {} ", constraints.join(", "));
+        }
+        write!(f, "}}")
+    }
+}
+
+impl<'a> AbstractState<'a> for Octagon<'a> {
+    type Value = Interval;
+    // relational domains keep plain intervals
+    type Context = ();
+
+    fn build(vars: HashSet<&'a str>, _ctx: &Self::Context) -> Self {
+        let mut vars: Vec<_> = vars.into_iter().collect();
+        vars.sort();
+        let dbm = Dbm::top(2 * vars.len());
+        Octagon {
+            vars,
+            dbm: close(dbm),
+        }
+    }
+
+    fn bottom() -> Self {
+        Octagon {
+            vars: vec![],
+            dbm: None,
+        }
+    }
+
+    fn is_bottom(&self) -> bool {
+        self.dbm.is_none()
+    }
+
+    fn lub(&self, other: &Self) -> Self {
+        match (&self.dbm, &other.dbm) {
+            (None, _) => other.clone(),
+            (_, None) => self.clone(),
+            (Some(lhs), Some(rhs)) => self.with_dbm(Some(lhs.join(rhs))),
+        }
+    }
+
+    fn glb(&self, other: &Self) -> Self {
+        match (&self.dbm, &other.dbm) {
+            (Some(lhs), Some(rhs)) => self.with_dbm(close(lhs.meet(rhs))),
+            _ => Self::bottom(),
+        }
+    }
+
+    // the result is left unclosed, closing it could break the termination of the iterations
+    fn widening(&self, rhs: &Self, thresholds: &HashSet<i64>) -> Self {
+        let (lhs_dbm, rhs_dbm) = match (&self.dbm, &rhs.dbm) {
+            (None, _) => return rhs.clone(),
+            (_, None) => return self.clone(),
+            (Some(lhs), Some(rhs)) => (lhs, rhs),
+        };
+
+        let thresholds: Vec<i64> = thresholds
+            .iter()
+            .flat_map(|t| [Some(*t), t.checked_neg()])
+            .flatten()
+            .collect();
+        let widened = lhs_dbm.widening(rhs_dbm, |i, j, bound| {
+            // unary constraints are stored doubled
+            let scale = if j == bar(i) { 2 } else { 1 };
+            thresholds
+                .iter()
+                .filter_map(|t| t.checked_mul(scale).map(Int::Num))
+                .filter(|t| *t >= bound)
+                .min()
+                .unwrap_or(Int::PosInf)
+        });
+        self.with_dbm(Some(widened))
+    }
+
+    fn narrowing(&self, rhs: &Self) -> Self {
+        match (&self.dbm, &rhs.dbm) {
+            (None, _) => rhs.clone(),
+            (_, None) => self.clone(),
+            (Some(lhs), Some(rhs)) => self.with_dbm(close(lhs.narrowing(rhs))),
+        }
+    }
+
+    fn value_of(&self, var: &str) -> Interval {
+        let Some(dbm) = &self.dbm else {
+            return Interval::bottom();
+        };
+        let k = self.index(var);
+        Interval::new(
+            -half(dbm.get(pos(k), neg(k))),
+            half(dbm.get(neg(k), pos(k))),
+        )
+    }
+
+    fn assign(&self, var: &'a str, value: &ArithmeticExp<'a>) -> Self {
+        let Some(dbm) = &self.dbm else {
+            return Self::bottom();
+        };
+        let k = self.index(var);
+        let mut dbm = dbm.clone();
+
+        let expr = LinearExpr::from_exp(value);
+        let vars: Vec<_> = expr
+            .iter()
+            .flat_map(|expr| expr.coeffs.iter().map(|(var, a)| (*var, *a)))
+            .collect();
+        match (expr.as_ref(), &vars[..]) {
+            // x := x + c translates every constraint on x
+            (Some(expr), [(y, 1)]) if *y == var => {
+                let c = Int::Num(expr.constant);
+                dbm.translate(pos(k), c);
+                dbm.translate(neg(k), -c);
+            }
+            // x := +-y + c
+            (Some(expr), [(y, a)]) if *y != var && a.abs() == 1 => {
+                let y = self.index(y);
+                Self::forget(&mut dbm, k);
+                add_difference(&mut dbm, term(y, *a), pos(k), expr.constant);
+                if let Some(c) = expr.constant.checked_neg() {
+                    add_difference(&mut dbm, pos(k), term(y, *a), c);
+                }
+            }
+            _ => {
+                let value = self.eval(value);
+                if value == Interval::bottom() {
+                    return Self::bottom();
+                }
+                Self::forget(&mut dbm, k);
+                Self::add_bounds(&mut dbm, k, value);
+            }
+        }
+        self.with_dbm(close(dbm))
+    }
+
+    fn filter(&self, cond: &ArithmeticCondition<'a>) -> Self {
+        let Some(dbm) = &self.dbm else {
+            return Self::bottom();
+        };
+
+        let Some(expr) = LinearExpr::from_exp(&cond.lhs) else {
+            return self.with_dbm(self.filter_intervals(dbm, cond));
+        };
+        if expr.coeffs.is_empty() {
+            let c = expr.constant;
+            let holds = match cond.operator {
+                ConditionOperator::Equal => c == 0,
+                ConditionOperator::NotEqual => c != 0,
+                ConditionOperator::StrictlyLess => c < 0,
+                ConditionOperator::GreaterOrEqual => c >= 0,
+            };
+            return match holds {
+                true => self.clone(),
+                false => Self::bottom(),
+            };
+        }
+
+        let mut refined = dbm.clone();
+        let octagonal = match cond.operator {
+            ConditionOperator::Equal => expr.scale(-1).is_some_and(|neg_expr| {
+                self.add_le_zero(&mut refined, &expr) && self.add_le_zero(&mut refined, &neg_expr)
+            }),
+            // over the integers e < 0 is e + 1 <= 0
+            ConditionOperator::StrictlyLess => expr
+                .add(&LinearExpr::constant(1))
+                .is_some_and(|expr| self.add_le_zero(&mut refined, &expr)),
+            ConditionOperator::GreaterOrEqual => expr
+                .scale(-1)
+                .is_some_and(|expr| self.add_le_zero(&mut refined, &expr)),
+            ConditionOperator::NotEqual => false,
+        };
+
+        match octagonal {
+            true => self.with_dbm(close(refined)),
+            false => self.with_dbm(self.filter_intervals(dbm, cond)),
+        }
+    }
+
+    fn vars(&self) -> HashSet<&'a str> {
+        self.vars.iter().copied().collect()
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use std::collections::HashSet;
+
+    use crate::{
+        abstract_domains::{abstract_domain::AbstractDomain, int::Int, interval::Interval},
+        abstract_states::{
+            abstract_state::AbstractState,
+            test_utils::{assign, build, cond},
+        },
+    };
+
+    use super::Octagon;
+
+    #[test]
+    fn octagon_filter() {
+        let s = build::<Octagon>(&["x", "y"], &["x >= 0", "x <= 10"]);
+        let s = s.filter(&cond("y < x"));
+        assert_eq!(s.value_of("y"), Interval::new(Int::NegInf, Int::Num(9)));
+        assert_eq!(s.to_string(), "{ x := [0,10] y := [-inf,9] | x - y >= 1 }");
+
+        let s = s.filter(&cond("x < y"));
+        assert!(s.is_bottom());
+    }
+
+    #[test]
+    fn octagon_assign() {
+        let s = build::<Octagon>(&["x", "y"], &["x >= 0", "x <= 10"]);
+        let s = assign(&s, "y", "x + 1");
+        assert_eq!(s.to_string(), "{ x := [0,10] y := [1,11] | x - y = -1 }");
+
+        let s = assign(&s, "x", "x + 2");
+        assert_eq!(s.to_string(), "{ x := [2,12] y := [1,11] | x - y = 1 }");
+
+        let s = assign(&s, "y", "x * x");
+        assert_eq!(s.to_string(), "{ x := [2,12] y := [4,144] }");
+    }
+
+    #[test]
+    fn octagon_sum() {
+        let s = build::<Octagon>(&["x", "y"], &[]);
+        let s = s.filter(&cond("x + y = 10")).filter(&cond("0 < x - 2"));
+        assert_eq!(s.value_of("y"), Interval::new(Int::NegInf, Int::Num(7)));
+        assert_eq!(s.to_string(), "{ x := [3,inf] y := [-inf,7] | x + y = 10 }");
+    }
+
+    #[test]
+    fn octagon_lattice() {
+        let s = build::<Octagon>(&["x", "y"], &[]);
+        let lhs = assign(&assign(&s, "x", "0"), "y", "0");
+        let rhs = assign(&assign(&s, "x", "1"), "y", "1");
+        let join = lhs.lub(&rhs);
+        assert_eq!(join.to_string(), "{ x := [0,1] y := [0,1] | x - y = 0 }");
+        assert_eq!(join.glb(&lhs), lhs);
+        assert!(lhs.glb(&rhs).is_bottom());
+
+        let widened = lhs.widening(&join, &HashSet::from([0]));
+        assert_eq!(
+            widened.to_string(),
+            "{ x := [0,inf] y := [0,inf] | x - y = 0 }"
+        );
+    }
+
+    #[test]
+    fn octagon_overflow() {
+        let s = build::<Octagon>(&["x", "y"], &["x >= 0", "x <= 10"]);
+        let shifted = assign(&s, "x", "x - 9223372036854775807 - 1");
+        let x = Interval::new(Int::Num(i64::MIN), Int::Num(i64::MIN + 10));
+        assert_eq!(shifted.value_of("x").lub(&x), shifted.value_of("x"));
+
+        let s = assign(&s, "y", "x - 9223372036854775807 - 1");
+        assert_eq!(s.value_of("y").lub(&x), s.value_of("y"));
+
+        let widened = s.widening(&assign(&s, "x", "x + 1"), &HashSet::from([i64::MIN]));
+        assert_eq!(widened.value_of("x").low(), Int::Num(0));
+    }
+}
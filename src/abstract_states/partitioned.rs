@@ -0,0 +1,258 @@
+use core::fmt;
+use std::collections::{BTreeMap, HashSet};
+
+use crate::{
+    abstract_domains::abstract_domain::AbstractDomain,
+    parser::ast::{ArithmeticCondition, ArithmeticExp, Position},
+};
+
+use super::abstract_state::AbstractState;
+
+// what a trace did at a partitioning point
+#[derive(Clone, Copy, Debug, PartialEq, Eq, PartialOrd, Ord)]
+pub enum Choice {
+    Then,
+    Else,
+    // exactly k iterations of an unrolled loop
+    Iteration(usize),
+    // at least k iterations, the loop was unrolled k times
+    AtLeast(usize),
+}
+
+impl fmt::Display for Choice {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        match self {
+            Choice::Then => write!(f, "then"),
+            Choice::Else => write!(f, "else"),
+            Choice::Iteration(k) => write!(f, "iteration {k}"),
+            Choice::AtLeast(k) => write!(f, "iteration {k}+"),
+        }
+    }
+}
+
+// the choices of a trace at every partitioning point it went through, a trace that goes through
+// the same point twice only remembers the last choice, so that there are finitely many partitions
+pub type Partition = BTreeMap<Position, Choice>;
+
+// trace partitioning: a state of S for each partition, the traces of different partitions are
+// never joined until the interpreter merges them
+#[derive(Clone, Debug, PartialEq)]
+pub struct Partitioned<S> {
+    // bottom states are never stored
+    partitions: BTreeMap<Partition, S>,
+}
+
+impl<'a, S: AbstractState<'a>> Partitioned<S> {
+    fn new(partitions: impl IntoIterator<Item = (Partition, S)>) -> Self {
+        let mut r: BTreeMap<Partition, S> = BTreeMap::new();
+        for (partition, state) in partitions {
+            if state.is_bottom() {
+                continue;
+            }
+            let state = match r.get(&partition) {
+                Some(current) => current.lub(&state),
+                None => state,
+            };
+            r.insert(partition, state);
+        }
+        Partitioned { partitions: r }
+    }
+
+    fn map(&self, f: impl Fn(&S) -> S) -> Self {
+        Self::new(
+            self.partitions
+                .iter()
+                .map(|(partition, state)| (partition.clone(), f(state))),
+        )
+    }
+
+    fn label(partition: &Partition) -> String {
+        let choices: Vec<_> = partition
+            .iter()
+            .map(|(pos, choice)| match choice {
+                Choice::Then | Choice::Else => format!("if@{} {choice}", pos.line + 1),
+                _ => format!("while@{} {choice}", pos.line + 1),
+            })
+            .collect();
+        format!("[{}]", choices.join(", "))
+    }
+}
+
+impl<'a, S: AbstractState<'a>> fmt::Display for Partitioned<S> {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        match self.partitions.len() {
+            0 => write!(f, "{}", S::bottom()),
+            1 if self.partitions.contains_key(&Partition::new()) => {
+                write!(f, "{}", self.partitions[&Partition::new()])
+            }
+            _ => {
+                let partitions: Vec<_> = self
+                    .partitions
+                    .iter()
+                    .map(|(partition, state)| format!("{} {state}", Self::label(partition)))
+                    .collect();
+                write!(f, "{}", partitions.join("\n"))
+            }
+        }
+    }
+}
+
+impl<'a, S: AbstractState<'a>> AbstractState<'a> for Partitioned<S> {
+    type Value = S::Value;
+    type Context = S::Context;
+
+    fn build(vars: HashSet<&'a str>, ctx: &Self::Context) -> Self {
+        Self::new([(Partition::new(), S::build(vars, ctx))])
+    }
+
+    fn bottom() -> Self {
+        Partitioned {
+            partitions: BTreeMap::new(),
+        }
+    }
+
+    fn is_bottom(&self) -> bool {
+        self.partitions.is_empty()
+    }
+
+    fn lub(&self, other: &Self) -> Self {
+        Self::new(
+            self.partitions
+                .iter()
+                .chain(other.partitions.iter())
+                .map(|(partition, state)| (partition.clone(), state.clone())),
+        )
+    }
+
+    // a trace belongs to a single partition, so only states of the same partition can meet
+    fn glb(&self, other: &Self) -> Self {
+        Self::new(self.partitions.iter().filter_map(|(partition, state)| {
+            other
+                .partitions
+                .get(partition)
+                .map(|rhs| (partition.clone(), state.glb(rhs)))
+        }))
+    }
+
+    // there are finitely many partitions, so widening each of them is enough to terminate
+    fn widening(&self, rhs: &Self, thresholds: &HashSet<i64>) -> Self {
+        Self::new(
+            self.partitions
+                .iter()
+                .chain(rhs.partitions.iter())
+                .map(|(partition, _)| {
+                    let state = match (
+                        self.partitions.get(partition),
+                        rhs.partitions.get(partition),
+                    ) {
+                        (Some(lhs), Some(rhs)) => lhs.widening(rhs, thresholds),
+                        (Some(state), None) | (None, Some(state)) => state.clone(),
+                        (None, None) => unreachable!(),
+                    };
+                    (partition.clone(), state)
+                }),
+        )
+    }
+
+    fn narrowing(&self, rhs: &Self) -> Self {
+        Self::new(rhs.partitions.iter().map(|(partition, state)| {
+            let state = match self.partitions.get(partition) {
+                Some(lhs) => lhs.narrowing(state),
+                None => state.clone(),
+            };
+            (partition.clone(), state)
+        }))
+    }
+
+    fn value_of(&self, var: &str) -> S::Value {
+        self.partitions
+            .values()
+            .fold(S::Value::bottom(), |acc, state| {
+                acc.lub(&state.value_of(var))
+            })
+    }
+
+    fn assign(&self, var: &'a str, value: &ArithmeticExp<'a>) -> Self {
+        self.map(|state| state.assign(var, value))
+    }
+
+    fn filter(&self, cond: &ArithmeticCondition<'a>) -> Self {
+        self.map(|state| state.filter(cond))
+    }
+
+    fn vars(&self) -> HashSet<&'a str> {
+        self.partitions
+            .values()
+            .next()
+            .map(|state| state.vars())
+            .unwrap_or_default()
+    }
+
+    fn eval(&self, exp: &ArithmeticExp<'a>) -> S::Value {
+        self.partitions
+            .values()
+            .fold(S::Value::bottom(), |acc, state| acc.lub(&state.eval(exp)))
+    }
+
+    fn partition(&self, pos: &Position, choice: Choice) -> Self {
+        Self::new(self.partitions.iter().map(|(partition, state)| {
+            let mut partition = partition.clone();
+            partition.insert(pos.clone(), choice);
+            (partition, state.clone())
+        }))
+    }
+
+    fn merge_partitions(&self) -> Self {
+        Self::new(
+            self.partitions
+                .values()
+                .map(|state| (Partition::new(), state.clone())),
+        )
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use std::collections::HashSet;
+
+    use crate::{
+        abstract_domains::sign::Sign,
+        abstract_states::{
+            abstract_state::AbstractState,
+            test_utils::{assign, cond},
+        },
+        parser::ast::Position,
+        state::State,
+    };
+
+    use super::{Choice, Partitioned};
+
+    #[test]
+    fn partitioned_conditional() {
+        let pos = Position { line: 2, clm: 0 };
+        let s: Partitioned<State<Sign>> = Partitioned::build(HashSet::from(["x", "y"]), &());
+        let then_branch = assign(&s.filter(&cond("x < 0")), "y", "0 - 1");
+        let else_branch = assign(&s.filter(&cond("x >= 0")), "y", "1");
+        let s = then_branch
+            .partition(&pos, Choice::Then)
+            .lub(&else_branch.partition(&pos, Choice::Else));
+        assert_eq!(s.partitions.len(), 2);
+        assert_eq!(s.filter(&cond("y < 0")).value_of("x"), Sign::Neg);
+
+        let merged = s.merge_partitions();
+        assert_eq!(merged.partitions.len(), 1);
+        assert_eq!(merged.filter(&cond("y < 0")).value_of("x"), Sign::Top);
+    }
+
+    #[test]
+    fn partitioned_same_point_twice() {
+        let pos = Position { line: 0, clm: 0 };
+        let s: Partitioned<State<Sign>> = Partitioned::build(HashSet::from(["x"]), &());
+        let s = s
+            .partition(&pos, Choice::Then)
+            .partition(&pos, Choice::Else);
+        assert_eq!(s.partitions.len(), 1);
+        assert!(s.partitions.keys().all(|p| p[&pos] == Choice::Else));
+        assert!(s.glb(&s.partition(&pos, Choice::Then)).is_bottom());
+    }
+}
@@ -0,0 +1,391 @@
+use core::fmt;
+use std::collections::HashSet;
+
+use crate::{
+    abstract_domains::{
+        abstract_domain::AbstractDomain,
+        int::Int,
+        interval::{Bounds, Interval},
+    },
+    linear_constraints::{
+        constraint::{Constraint, ConstraintKind},
+        polyhedron::Polyhedron,
+        rational::{Rational, gcd},
+    },
+    parser::ast::{ArithmeticCondition, ArithmeticExp, ConditionOperator},
+    state::State,
+};
+
+use super::{abstract_state::AbstractState, linear::LinearExpr};
+
+fn to_int(bound: Option<i128>, infinity: Int) -> Int {
+    bound
+        .and_then(|x| i64::try_from(x).ok())
+        .map_or(infinity, Int::Num)
+}
+
+#[derive(Clone, Debug)]
+pub struct Polyhedra<'a> {
+    vars: Vec<&'a str>,
+    // the variable in position k is the dimension k + 1 of the polyhedron
+    poly: Polyhedron,
+}
+
+impl<'a> PartialEq for Polyhedra<'a> {
+    fn eq(&self, other: &Self) -> bool {
+        match (self.is_bottom(), other.is_bottom()) {
+            (true, true) => true,
+            (false, false) => self.vars == other.vars && self.poly == other.poly,
+            _ => false,
+        }
+    }
+}
+
+impl<'a> Polyhedra<'a> {
+    fn index(&self, var: &str) -> usize {
+        self.vars
+            .binary_search(&var)
+            .map(|k| k + 1)
+            .unwrap_or_else(|_| panic!("Variable {var} not tracked by the polyhedra"))
+    }
+
+    fn with_poly(&self, poly: Polyhedron) -> Self {
+        Polyhedra {
+            vars: self.vars.clone(),
+            poly,
+        }
+    }
+
+    fn row(&self, expr: &LinearExpr) -> Vec<Rational> {
+        let mut row = vec![Rational::ZERO; self.vars.len() + 1];
+        row[0] = Rational::from(expr.constant);
+        expr.coeffs
+            .iter()
+            .for_each(|(var, a)| row[self.index(var)] = Rational::from(*a));
+        row
+    }
+
+    // over the integers a.x + b >= 0 is (a / g).x + floor(b / g) >= 0 with g the gcd of a,
+    // while a.x + b = 0 has no solution unless g divides b
+    fn integer_constraint(&self, kind: ConstraintKind, expr: &LinearExpr) -> Constraint {
+        let mut row = self.row(expr);
+        let g = row[1..].iter().fold(0, |acc, a| gcd(acc, a.num()));
+        if g > 1 {
+            // the row is made of integers, so the divisions are exact or rounded
+            row[1..]
+                .iter_mut()
+                .for_each(|a| *a = Rational::from(a.num() / g));
+            let constant = Rational::new(row[0].num(), g);
+            row[0] = match kind {
+                ConstraintKind::Inequality => Rational::from(constant.floor()),
+                ConstraintKind::Equality if constant.den() != 1 => {
+                    return Constraint::inequality(
+                        std::iter::once(-Rational::ONE)
+                            .chain(row[1..].iter().map(|_| Rational::ZERO))
+                            .collect(),
+                    );
+                }
+                ConstraintKind::Equality => constant,
+            };
+        }
+        match kind {
+            ConstraintKind::Equality => Constraint::equality(row),
+            ConstraintKind::Inequality => Constraint::inequality(row),
+        }
+    }
+
+    fn bounds_constraints(&self, k: usize, value: Interval) -> Vec<Constraint> {
+        let mut constraints = vec![];
+        if let Int::Num(upper) = value.upper() {
+            let mut row = vec![Rational::ZERO; self.vars.len() + 1];
+            row[0] = Rational::from(upper);
+            row[k] = -Rational::ONE;
+            constraints.push(Constraint::inequality(row));
+        }
+        if let Int::Num(low) = value.low() {
+            let mut row = vec![Rational::ZERO; self.vars.len() + 1];
+            row[0] = -Rational::from(low);
+            row[k] = Rational::ONE;
+            constraints.push(Constraint::inequality(row));
+        }
+        constraints
+    }
+
+    // e >= 0
+    fn add_nonnegative(&self, expr: &LinearExpr) -> Self {
+        self.with_poly(self.poly.add_constraints(vec![
+            self.integer_constraint(ConstraintKind::Inequality, expr),
+        ]))
+    }
+
+    // refines the polyhedron with the non-relational propagation algorithm on its interval projection
+    fn filter_intervals(&self, cond: &ArithmeticCondition<'a>) -> Self {
+        let intervals = State::new(
+            self.vars
+                .iter()
+                .map(|var| (*var, self.value_of(var)))
+                .collect(),
+            Bounds::default(),
+        );
+        let refined = intervals.filter(cond);
+        if refined.is_bottom() {
+            return Self::bottom();
+        }
+
+        let constraints = self
+            .vars
+            .iter()
+            .flat_map(|var| self.bounds_constraints(self.index(var), *refined.lookup(var)))
+            .collect();
+        self.with_poly(self.poly.add_constraints(constraints))
+    }
+
+    fn constraint_to_string(&self, constraint: &Constraint) -> String {
+        let mut coeffs = constraint.coeffs.clone();
+        // the first variable gets a positive coefficient, flipping the inequality if needed
+        let flip = coeffs[1..]
+            .iter()
+            .find(|a| !a.is_zero())
+            .is_some_and(|a| *a < Rational::ZERO);
+        if flip {
+            coeffs.iter_mut().for_each(|a| *a = -*a);
+        }
+
+        let mut lhs = String::new();
+        for (var, a) in self.vars.iter().zip(coeffs[1..].iter()) {
+            if a.is_zero() {
+                continue;
+            }
+            let sign = match (lhs.is_empty(), *a < Rational::ZERO) {
+                (true, false) => "",
+                (true, true) => "-",
+                (false, false) => " + ",
+                (false, true) => " - ",
+            };
+            let a = a.abs();
+            match a == Rational::ONE {
+                true => lhs.push_str(&format!("{sign}{var}")),
+                false => lhs.push_str(&format!("{sign}{a}{var}")),
+            }
+        }
+
+        let operator = match (constraint.kind, flip) {
+            (ConstraintKind::Equality, _) => "=",
+            (ConstraintKind::Inequality, false) => ">=",
+            (ConstraintKind::Inequality, true) => "<=",
+        };
+        format!("{lhs} {operator} {}", -coeffs[0])
+    }
+}
+
+impl<'a> fmt::Display for Polyhedra<'a> {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        if self.is_bottom() {
+            return write!(f, "{{ }}");
+        }
+        let constraints: Vec<_> = self
+            .poly
+            .constraints()
+            .iter()
+            .map(|c| self.constraint_to_string(c))
+            .collect();
+        match constraints.is_empty() {
+            true => write!(f, "{{ true }}"),
+            false => write!(f, "{{ {} }}", constraints.join(" & ")),
+        }
+    }
+}
+
+impl<'a> AbstractState<'a> for Polyhedra<'a> {
+    type Value = Interval;
+    // relational domains keep plain intervals
+    type Context = ();
+
+    fn build(vars: HashSet<&'a str>, _ctx: &Self::Context) -> Self {
+        let mut vars: Vec<_> = vars.into_iter().collect();
+        vars.sort();
+        Polyhedra {
+            poly: Polyhedron::universe(vars.len()),
+            vars,
+        }
+    }
+
+    fn bottom() -> Self {
+        Polyhedra {
+            vars: vec![],
+            poly: Polyhedron::empty(0),
+        }
+    }
+
+    fn is_bottom(&self) -> bool {
+        self.poly.is_empty()
+    }
+
+    fn lub(&self, other: &Self) -> Self {
+        match (self.is_bottom(), other.is_bottom()) {
+            (true, _) => other.clone(),
+            (_, true) => self.clone(),
+            _ => self.with_poly(self.poly.join(&other.poly)),
+        }
+    }
+
+    fn glb(&self, other: &Self) -> Self {
+        match self.is_bottom() || other.is_bottom() {
+            true => Self::bottom(),
+            false => self.with_poly(self.poly.meet(&other.poly)),
+        }
+    }
+
+    // standard widening, the constants of the program are not used as thresholds
+    fn widening(&self, rhs: &Self, _thresholds: &HashSet<i64>) -> Self {
+        match (self.is_bottom(), rhs.is_bottom()) {
+            (true, _) => rhs.clone(),
+            (_, true) => self.clone(),
+            _ => self.with_poly(self.poly.widening(&rhs.poly)),
+        }
+    }
+
+    // the number of narrowing steps is bounded by the interpreter, so the meet is enough
+    fn narrowing(&self, rhs: &Self) -> Self {
+        match (self.is_bottom(), rhs.is_bottom()) {
+            (true, _) => rhs.clone(),
+            (_, true) => self.clone(),
+            _ => self.glb(rhs),
+        }
+    }
+
+    fn value_of(&self, var: &str) -> Interval {
+        if self.is_bottom() {
+            return Interval::bottom();
+        }
+        let (low, upper) = self.poly.bounds(self.index(var));
+        Interval::new(
+            to_int(low.map(|l| l.ceil()), Int::NegInf),
+            to_int(upper.map(|u| u.floor()), Int::PosInf),
+        )
+    }
+
+    fn assign(&self, var: &'a str, value: &ArithmeticExp<'a>) -> Self {
+        if self.is_bottom() {
+            return Self::bottom();
+        }
+        let k = self.index(var);
+        if let Some(expr) = LinearExpr::from_exp(value) {
+            return self.with_poly(self.poly.affine_image(k, &self.row(&expr)));
+        }
+
+        let value = self.eval(value);
+        if value == Interval::bottom() {
+            return Self::bottom();
+        }
+        self.with_poly(
+            self.poly
+                .forget(k)
+                .add_constraints(self.bounds_constraints(k, value)),
+        )
+    }
+
+    fn filter(&self, cond: &ArithmeticCondition<'a>) -> Self {
+        if self.is_bottom() {
+            return Self::bottom();
+        }
+        let Some(expr) = LinearExpr::from_exp(&cond.lhs) else {
+            return self.filter_intervals(cond);
+        };
+        // e < 0 is -e - 1 >= 0 over the integers
+        let less = expr
+            .scale(-1)
+            .and_then(|e| e.add(&LinearExpr::constant(-1)));
+        let greater = expr.add(&LinearExpr::constant(-1));
+
+        match (cond.operator, less, greater) {
+            (ConditionOperator::GreaterOrEqual, _, _) => self.add_nonnegative(&expr),
+            (ConditionOperator::Equal, _, _) => self.with_poly(self.poly.add_constraints(vec![
+                self.integer_constraint(ConstraintKind::Equality, &expr),
+            ])),
+            (ConditionOperator::StrictlyLess, Some(less), _) => self.add_nonnegative(&less),
+            // the convex hull of e < 0 and e > 0
+            (ConditionOperator::NotEqual, Some(less), Some(greater)) => self
+                .add_nonnegative(&less)
+                .lub(&self.add_nonnegative(&greater)),
+            _ => self.filter_intervals(cond),
+        }
+    }
+
+    fn vars(&self) -> HashSet<&'a str> {
+        self.vars.iter().copied().collect()
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use std::collections::HashSet;
+
+    use crate::{
+        abstract_domains::{abstract_domain::AbstractDomain, int::Int, interval::Interval},
+        abstract_states::{
+            abstract_state::AbstractState,
+            test_utils::{assign, build, cond},
+        },
+    };
+
+    use super::Polyhedra;
+
+    #[test]
+    fn polyhedra_filter() {
+        let s = build::<Polyhedra>(&["x", "y"], &["x >= 0", "x <= 10"]);
+        let s = s.filter(&cond("2 * y < x"));
+        assert_eq!(s.value_of("y"), Interval::new(Int::NegInf, Int::Num(4)));
+
+        let s = s.filter(&cond("x < 2 * y"));
+        assert!(s.is_bottom());
+
+        let s = build::<Polyhedra>(&["x"], &[]).filter(&cond("2 * x = 1"));
+        assert!(s.is_bottom());
+    }
+
+    #[test]
+    fn polyhedra_assign() {
+        let s = build::<Polyhedra>(&["n", "i", "j"], &["n <= 100", "n >= 0"]);
+        let s = assign(&assign(&s, "i", "0"), "j", "n");
+        let s = assign(&assign(&s, "i", "i + 1"), "j", "j - 1");
+        assert_eq!(s.to_string(), "{ j - n = -1 & i = 1 & n <= 100 & n >= 0 }");
+        assert_eq!(s.value_of("j"), Interval::new(Int::Num(-1), Int::Num(99)));
+
+        let s = assign(&s, "j", "i * n");
+        assert_eq!(s.value_of("j"), Interval::new(Int::Num(0), Int::Num(100)));
+    }
+
+    #[test]
+    fn polyhedra_lattice() {
+        let s = build::<Polyhedra>(&["x", "y"], &[]);
+        let lhs = assign(&assign(&s, "x", "0"), "y", "0");
+        let rhs = assign(&assign(&s, "x", "1"), "y", "1");
+        let join = lhs.lub(&rhs);
+        assert_eq!(join.glb(&lhs), lhs);
+        assert!(lhs.glb(&rhs).is_bottom());
+        assert!(!join.filter(&cond("x != 0")).is_bottom());
+        assert_eq!(join.filter(&cond("x != 0")), rhs);
+
+        let widened = lhs.widening(&join, &HashSet::new());
+        assert_eq!(widened.to_string(), "{ x - y = 0 & x >= 0 }");
+        assert_eq!(build::<Polyhedra>(&["x"], &[]).to_string(), "{ true }");
+    }
+
+    #[test]
+    fn polyhedra_overflow() {
+        // the double description overflows with the third constraint, which is dropped
+        let s = build::<Polyhedra>(&["x", "y"], &[])
+            .filter(&cond("3 * x + 7 * y <= 1000000000000000000"))
+            .filter(&cond("5 * x - 11 * y >= 999999999999999999"))
+            .filter(&cond("x - 13 * y <= 777777777777777777"));
+        assert!(!s.is_bottom());
+        assert_eq!(
+            s.to_string(),
+            "{ 3x + 7y <= 1000000000000000000 & 5x - 11y >= 999999999999999999 }"
+        );
+
+        let s = assign(&s, "x", "x * 1000000000000000000 + 3 * y");
+        assert_eq!(s.value_of("x"), Interval::top());
+    }
+}
@@ -0,0 +1,352 @@
+use core::fmt;
+use std::{collections::HashSet, fmt::Debug, marker::PhantomData};
+
+use crate::{
+    abstract_domains::abstract_domain::AbstractDomain,
+    parser::ast::{ArithmeticCondition, ArithmeticExp},
+};
+
+use super::abstract_state::AbstractState;
+
+fn leq<'a, S: AbstractState<'a>>(lhs: &S, rhs: &S) -> bool {
+    lhs.lub(rhs) == *rhs
+}
+
+fn hull<'a, S: AbstractState<'a>>(disjuncts: &[S]) -> S {
+    disjuncts
+        .iter()
+        .fold(S::bottom(), |acc, disjunct| acc.lub(disjunct))
+}
+
+// chooses the two disjuncts to merge when there are more than K of them
+pub trait MergeHeuristic: Clone + Copy + Debug {
+    fn select<'a, S: AbstractState<'a>>(disjuncts: &[S]) -> (usize, usize);
+}
+
+// merges the two disjuncts that have been in the set for the longest time
+#[derive(Clone, Copy, Debug)]
+pub struct MergeOldest;
+
+impl MergeHeuristic for MergeOldest {
+    fn select<'a, S: AbstractState<'a>>(_disjuncts: &[S]) -> (usize, usize) {
+        (0, 1)
+    }
+}
+
+// merges the two disjuncts whose join changes the value of the fewest variables
+#[derive(Clone, Copy, Debug)]
+pub struct MergeClosest;
+
+impl MergeHeuristic for MergeClosest {
+    fn select<'a, S: AbstractState<'a>>(disjuncts: &[S]) -> (usize, usize) {
+        let distance = |lhs: &S, rhs: &S| {
+            let join = lhs.lub(rhs);
+            join.vars()
+                .iter()
+                .map(|var| {
+                    let value = join.value_of(var);
+                    usize::from(value != lhs.value_of(var))
+                        + usize::from(value != rhs.value_of(var))
+                })
+                .sum::<usize>()
+        };
+        (0..disjuncts.len())
+            .flat_map(|i| (i + 1..disjuncts.len()).map(move |j| (i, j)))
+            .min_by_key(|(i, j)| distance(&disjuncts[*i], &disjuncts[*j]))
+            .expect("At least two disjuncts are needed to merge")
+    }
+}
+
+// finite disjunctions of at most K elements of S, bottom is the empty disjunction
+#[derive(Clone, Debug)]
+pub struct Powerset<S, const K: usize, H = MergeClosest> {
+    // never bottom and never included in one another, ordered from the oldest
+    disjuncts: Vec<S>,
+    // left by a widening: the hull widened along the iterations, and how many extrapolations
+    // have been kept since it last changed
+    certificate: Option<(S, usize)>,
+    heuristic: PhantomData<H>,
+}
+
+impl<'a, S: AbstractState<'a>, const K: usize, H: MergeHeuristic> PartialEq for Powerset<S, K, H> {
+    fn eq(&self, other: &Self) -> bool {
+        self.disjuncts.len() == other.disjuncts.len()
+            && self.disjuncts.iter().all(|d| other.disjuncts.contains(d))
+    }
+}
+
+impl<'a, S: AbstractState<'a>, const K: usize, H: MergeHeuristic> Powerset<S, K, H> {
+    pub fn new(disjuncts: Vec<S>) -> Self {
+        assert!(K > 0, "A powerset needs room for at least one disjunct");
+        let mut reduced: Vec<S> = vec![];
+        for disjunct in disjuncts.into_iter().filter(|d| !d.is_bottom()) {
+            if reduced.iter().any(|r| leq(&disjunct, r)) {
+                continue;
+            }
+            reduced.retain(|r| !leq(r, &disjunct));
+            reduced.push(disjunct);
+        }
+
+        while reduced.len() > K {
+            let (i, j) = H::select(&reduced);
+            let merged = reduced[i].lub(&reduced[j]);
+            reduced.remove(j);
+            reduced.remove(i);
+            reduced.retain(|r| !leq(r, &merged));
+            reduced.push(merged);
+        }
+
+        Powerset {
+            disjuncts: reduced,
+            certificate: None,
+            heuristic: PhantomData,
+        }
+    }
+
+    fn map(&self, f: impl Fn(&S) -> S) -> Self {
+        Self::new(self.disjuncts.iter().map(f).collect())
+    }
+
+    // every disjunct of other is included in a disjunct of self
+    fn includes(&self, other: &Self) -> bool {
+        other
+            .disjuncts
+            .iter()
+            .all(|o| self.disjuncts.iter().any(|s| leq(o, s)))
+    }
+}
+
+impl<'a, S: AbstractState<'a>, const K: usize, H: MergeHeuristic> fmt::Display
+    for Powerset<S, K, H>
+{
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        if self.disjuncts.is_empty() {
+            return write!(f, "{}", S::bottom());
+        }
+        let disjuncts: Vec<_> = self.disjuncts.iter().map(|d| d.to_string()).collect();
+        write!(f, "{}", disjuncts.join("\n∨ "))
+    }
+}
+
+impl<'a, S: AbstractState<'a>, const K: usize, H: MergeHeuristic> AbstractState<'a>
+    for Powerset<S, K, H>
+{
+    type Value = S::Value;
+    type Context = S::Context;
+
+    fn build(vars: HashSet<&'a str>, ctx: &Self::Context) -> Self {
+        Self::new(vec![S::build(vars, ctx)])
+    }
+
+    fn bottom() -> Self {
+        Self::new(vec![])
+    }
+
+    fn is_bottom(&self) -> bool {
+        self.disjuncts.is_empty()
+    }
+
+    fn lub(&self, other: &Self) -> Self {
+        Self::new(
+            self.disjuncts
+                .iter()
+                .chain(other.disjuncts.iter())
+                .cloned()
+                .collect(),
+        )
+    }
+
+    fn glb(&self, other: &Self) -> Self {
+        Self::new(
+            self.disjuncts
+                .iter()
+                .flat_map(|lhs| other.disjuncts.iter().map(|rhs| lhs.glb(rhs)))
+                .collect(),
+        )
+    }
+
+    // Bagnara–Hill–Zaffanella extrapolation: each new disjunct is widened against the old ones
+    // it covers, while the others are kept as they are. The hull certifies the termination: it
+    // is widened at each step, and once it is stable the disjuncts are kept for at most K more
+    // extrapolations before collapsing into it
+    fn widening(&self, rhs: &Self, thresholds: &HashSet<i64>) -> Self {
+        if self.is_bottom() {
+            return rhs.clone();
+        }
+        if self.includes(rhs) {
+            return self.clone();
+        }
+
+        let (lhs_hull, kept) = self
+            .certificate
+            .clone()
+            .unwrap_or_else(|| (hull(&self.disjuncts), 0));
+        let widened_hull = lhs_hull.widening(&lhs_hull.lub(&hull(&rhs.disjuncts)), thresholds);
+        let kept = match widened_hull == lhs_hull {
+            true => kept + 1,
+            false => 0,
+        };
+
+        let mut widened = match kept > K {
+            true => Self::new(vec![widened_hull.clone()]),
+            false => Self::new(
+                rhs.disjuncts
+                    .iter()
+                    .map(|r| {
+                        let covered: Vec<_> = self
+                            .disjuncts
+                            .iter()
+                            .filter(|l| leq(*l, r))
+                            .cloned()
+                            .collect();
+                        match covered.is_empty() {
+                            true => r.clone(),
+                            false => hull(&covered).widening(r, thresholds),
+                        }
+                    })
+                    .collect(),
+            ),
+        };
+        widened.certificate = Some((widened_hull, kept));
+        widened
+    }
+
+    // the number of narrowing steps is bounded by the interpreter, so any sound refinement works
+    fn narrowing(&self, rhs: &Self) -> Self {
+        match (&self.disjuncts[..], &rhs.disjuncts[..]) {
+            ([], _) => rhs.clone(),
+            (_, []) => self.clone(),
+            ([lhs], [rhs]) => Self::new(vec![lhs.narrowing(rhs)]),
+            _ if self.includes(rhs) => rhs.clone(),
+            _ => self.clone(),
+        }
+    }
+
+    fn value_of(&self, var: &str) -> S::Value {
+        self.disjuncts
+            .iter()
+            .fold(S::Value::bottom(), |acc, d| acc.lub(&d.value_of(var)))
+    }
+
+    fn assign(&self, var: &'a str, value: &ArithmeticExp<'a>) -> Self {
+        self.map(|d| d.assign(var, value))
+    }
+
+    fn filter(&self, cond: &ArithmeticCondition<'a>) -> Self {
+        self.map(|d| d.filter(cond))
+    }
+
+    fn vars(&self) -> HashSet<&'a str> {
+        self.disjuncts.first().map(|d| d.vars()).unwrap_or_default()
+    }
+
+    fn eval(&self, exp: &ArithmeticExp<'a>) -> S::Value {
+        self.disjuncts
+            .iter()
+            .fold(S::Value::bottom(), |acc, d| acc.lub(&d.eval(exp)))
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use std::collections::HashSet;
+
+    use crate::{
+        abstract_domains::{int::Int, interval::Interval, sign::Sign},
+        abstract_states::{
+            abstract_state::AbstractState,
+            test_utils::{assign, cond},
+        },
+        state::State,
+    };
+
+    use super::{MergeClosest, MergeHeuristic, MergeOldest, Powerset};
+
+    type Disjunctive<D, const K: usize, H = MergeClosest> = Powerset<State<'static, D>, K, H>;
+
+    fn signs(vars: &[&'static str]) -> Disjunctive<Sign, 2> {
+        Powerset::build(vars.iter().copied().collect(), &())
+    }
+
+    #[test]
+    fn powerset_keeps_branches_apart() {
+        // if x < 0 then y := -1 else y := 1
+        let s = signs(&["x", "y"]);
+        let then_branch = assign(&s.filter(&cond("x < 0")), "y", "0 - 1");
+        let else_branch = assign(&s.filter(&cond("x >= 0")), "y", "1");
+        let s = then_branch.lub(&else_branch);
+        assert_eq!(s.disjuncts.len(), 2);
+        assert_eq!(s.value_of("y"), Sign::NonZero);
+
+        let y_neg = s.filter(&cond("y < 0"));
+        assert_eq!(y_neg.value_of("x"), Sign::Neg);
+        // the join of the two branches forgets that y < 0 only when x < 0
+        let join = then_branch.disjuncts[0].lub(&else_branch.disjuncts[0]);
+        assert_eq!(join.filter(&cond("y < 0")).value_of("x"), Sign::Top);
+        assert_eq!(s, else_branch.lub(&then_branch));
+    }
+
+    #[test]
+    fn powerset_merge_heuristics() {
+        fn three_points<H: MergeHeuristic>() -> Disjunctive<Interval, 2, H> {
+            let s: Disjunctive<Interval, 2, H> =
+                Powerset::build(HashSet::from(["x", "y"]), &Default::default());
+            let point = |x, y| assign(&assign(&s, "x", x), "y", y);
+            point("0", "5")
+                .lub(&point("10", "0"))
+                .lub(&point("11", "0"))
+        }
+        let interval = |low, upper| Interval::new(Int::Num(low), Int::Num(upper));
+
+        let s = three_points::<MergeOldest>();
+        assert_eq!(s.disjuncts.len(), 2);
+        assert_eq!(s.disjuncts[1].value_of("x"), interval(0, 10));
+        assert_eq!(s.disjuncts[1].value_of("y"), interval(0, 5));
+
+        let s = three_points::<MergeClosest>();
+        assert_eq!(s.disjuncts.len(), 2);
+        assert_eq!(s.disjuncts[1].value_of("x"), interval(10, 11));
+        assert_eq!(s.disjuncts[1].value_of("y"), interval(0, 0));
+        assert_eq!(s.value_of("x"), interval(0, 11));
+    }
+
+    #[test]
+    fn powerset_widening() {
+        let s: Disjunctive<Sign, 2> = signs(&["x"]);
+        let lhs = assign(&s, "x", "0");
+        let rhs = lhs.lub(&assign(&s, "x", "1"));
+        // Sign has no widening, the widened hull is the join and the new disjunct is kept
+        assert_eq!(lhs.widening(&rhs, &HashSet::new()), rhs);
+        assert_eq!(rhs.widening(&lhs, &HashSet::new()), rhs);
+    }
+
+    #[test]
+    fn powerset_widening_keeps_disjuncts() {
+        // x := 0; y := 0; while x < 10 do { if x < 5 then y := -1 else y := 1; x := x + 1 }
+        let s: Disjunctive<Interval, 4> =
+            Powerset::build(HashSet::from(["x", "y"]), &Default::default());
+        let init = assign(&assign(&s, "x", "0"), "y", "0");
+        let body = |s: &Disjunctive<Interval, 4>| {
+            let s = s.filter(&cond("x < 10"));
+            let then_branch = assign(&s.filter(&cond("x < 5")), "y", "0 - 1");
+            let else_branch = assign(&s.filter(&cond("x >= 5")), "y", "1");
+            assign(&then_branch.lub(&else_branch), "x", "x + 1")
+        };
+
+        let thresholds = HashSet::from([0, 1, 5, 10]);
+        let mut x = init.clone();
+        loop {
+            let next = x.widening(&init.lub(&body(&x)), &thresholds);
+            if next == x {
+                break;
+            }
+            x = next;
+        }
+
+        let interval = |low, upper| Interval::new(Int::Num(low), Int::Num(upper));
+        assert_eq!(x.disjuncts.len(), 3);
+        assert_eq!(x.value_of("y"), interval(-1, 1));
+        assert_eq!(x.filter(&cond("y < 0")).value_of("x"), interval(1, 5));
+        assert_eq!(x.filter(&cond("0 < y")).value_of("x"), interval(6, 10));
+    }
+}
@@ -0,0 +1,36 @@
+// fixtures shared by the tests of the abstract states
+use crate::{
+    abstract_states::abstract_state::AbstractState,
+    grammar::{ArithmeticExpParser, BooleanExpParser},
+    parser::{
+        ast::{ArithmeticCondition, BooleanExp},
+        lexer::Lexer,
+    },
+};
+
+pub fn cond(src: &'static str) -> ArithmeticCondition<'static> {
+    match *BooleanExpParser::new()
+        .parse(src, &mut vec![], Lexer::new(src))
+        .unwrap()
+    {
+        BooleanExp::ArithmeticCondition(cond) => cond,
+        _ => panic!("{src} is not an arithmetic condition"),
+    }
+}
+
+pub fn assign<S: AbstractState<'static>>(state: &S, var: &'static str, src: &'static str) -> S {
+    state.assign(
+        var,
+        &ArithmeticExpParser::new()
+            .parse(src, &mut vec![], Lexer::new(src))
+            .unwrap(),
+    )
+}
+
+// a state of vars where the conditions hold
+pub fn build<S: AbstractState<'static>>(vars: &[&'static str], conds: &[&'static str]) -> S {
+    let state = S::build(vars.iter().copied().collect(), &S::Context::default());
+    conds
+        .iter()
+        .fold(state, |state, src| state.filter(&cond(src)))
+}
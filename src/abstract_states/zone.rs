@@ -0,0 +1,374 @@
+use core::fmt;
+use std::collections::HashSet;
+
+use crate::{
+    abstract_domains::{abstract_domain::AbstractDomain, int::Int, interval::Interval},
+    parser::ast::{ArithmeticCondition, ArithmeticExp, ConditionOperator},
+};
+
+use super::{
+    abstract_state::AbstractState,
+    dbm::{self, Dbm},
+    linear::LinearExpr,
+};
+
+// index 0 is the constant zero, so that x <= c is the difference x - 0 <= c
+const ZERO: usize = 0;
+
+fn close(mut dbm: Dbm) -> Option<Dbm> {
+    dbm.shortest_paths().then_some(dbm)
+}
+
+#[derive(Clone, Debug)]
+pub struct Zone<'a> {
+    vars: Vec<&'a str>,
+    // None is bottom, otherwise always closed but after a widening
+    dbm: Option<Dbm>,
+}
+
+impl<'a> PartialEq for Zone<'a> {
+    fn eq(&self, other: &Self) -> bool {
+        match (&self.dbm, &other.dbm) {
+            (None, None) => true,
+            (Some(lhs), Some(rhs)) => self.vars == other.vars && lhs == rhs,
+            _ => false,
+        }
+    }
+}
+
+impl<'a> Zone<'a> {
+    fn index(&self, var: &str) -> usize {
+        self.vars
+            .binary_search(&var)
+            .map(|k| k + 1)
+            .unwrap_or_else(|_| panic!("Variable {var} not tracked by the zone"))
+    }
+
+    fn with_dbm(&self, dbm: Option<Dbm>) -> Self {
+        Zone {
+            vars: self.vars.clone(),
+            dbm,
+        }
+    }
+
+    // adds expr <= 0, false when it is not a difference constraint
+    fn add_le_zero(&self, dbm: &mut Dbm, expr: &LinearExpr) -> bool {
+        let Some(c) = expr.constant.checked_neg() else {
+            return false;
+        };
+        let terms: Vec<_> = expr
+            .coeffs
+            .iter()
+            .map(|(var, a)| (self.index(var), *a))
+            .collect();
+        let (i, j) = match terms[..] {
+            [(x, 1)] => (ZERO, x),
+            [(x, -1)] => (x, ZERO),
+            [(x, 1), (y, -1)] | [(y, -1), (x, 1)] => (y, x),
+            _ => return false,
+        };
+        dbm.tighten(i, j, Int::Num(c));
+        true
+    }
+
+    fn add_bounds(dbm: &mut Dbm, k: usize, value: Interval) {
+        if let Int::Num(upper) = value.upper() {
+            dbm.tighten(ZERO, k, Int::Num(upper));
+        }
+        if let Int::Num(low) = value.low() {
+            dbm.tighten(k, ZERO, -Int::Num(low));
+        }
+    }
+
+    // refines the zone with the non-relational propagation algorithm on its interval projection
+    fn filter_intervals(&self, dbm: &Dbm, cond: &ArithmeticCondition<'a>) -> Option<Dbm> {
+        let refined = dbm::filter_intervals(&self.vars, |var| self.value_of(var), cond)?;
+        let mut dbm = dbm.clone();
+        refined
+            .into_iter()
+            .enumerate()
+            .for_each(|(k, value)| Self::add_bounds(&mut dbm, k + 1, value));
+        close(dbm)
+    }
+
+    fn relational_bound(dbm: &Dbm, i: usize, j: usize) -> Option<i64> {
+        dbm::relational_bound(dbm, i, j, dbm.get(i, ZERO) + dbm.get(ZERO, j))
+    }
+
+    fn relational_constraints(&self, dbm: &Dbm) -> Vec<String> {
+        let mut bounds = vec![];
+        for (a, x) in self.vars.iter().enumerate() {
+            for (b, y) in self.vars.iter().enumerate().skip(a + 1) {
+                bounds.push((
+                    format!("{x} - {y}"),
+                    Self::relational_bound(dbm, b + 1, a + 1),
+                    Self::relational_bound(dbm, a + 1, b + 1),
+                ));
+            }
+        }
+        dbm::relational_constraints(bounds)
+    }
+}
+
+impl<'a> fmt::Display for Zone<'a> {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        let Some(dbm) = self.dbm.clone().and_then(close) else {
+            return write!(f, "{{ }}");
+        };
+        let closed = self.with_dbm(Some(dbm.clone()));
+
+        let _ = write!(f, "{{ ");
+        self.vars.iter().for_each(|var| {
+            let _ = write!(
+                f,
+                "{var} := {} ",
+                Into::<String>::into(closed.value_of(var))
+            );
+        });
+        let constraints = self.relational_constraints(&dbm);
+        if !constraints.is_empty() {
+            let _ = write!(f, "| {} ", constraints.join(", "));
+        }
+        write!(f, "}}")
+    }
+}
+
+impl<'a> AbstractState<'a> for Zone<'a> {
+    type Value = Interval;
+    // relational domains keep plain intervals
+    type Context = ();
+
+    fn build(vars: HashSet<&'a str>, _ctx: &Self::Context) -> Self {
+        let mut vars: Vec<_> = vars.into_iter().collect();
+        vars.sort();
+        let dbm = Dbm::top(vars.len() + 1);
+        Zone {
+            vars,
+            dbm: close(dbm),
+        }
+    }
+
+    fn bottom() -> Self {
+        Zone {
+            vars: vec![],
+            dbm: None,
+        }
+    }
+
+    fn is_bottom(&self) -> bool {
+        self.dbm.is_none()
+    }
+
+    fn lub(&self, other: &Self) -> Self {
+        match (&self.dbm, &other.dbm) {
+            (None, _) => other.clone(),
+            (_, None) => self.clone(),
+            (Some(lhs), Some(rhs)) => self.with_dbm(Some(lhs.join(rhs))),
+        }
+    }
+
+    fn glb(&self, other: &Self) -> Self {
+        match (&self.dbm, &other.dbm) {
+            (Some(lhs), Some(rhs)) => self.with_dbm(close(lhs.meet(rhs))),
+            _ => Self::bottom(),
+        }
+    }
+
+    // unstable bounds jump to the next program constant, the result is left unclosed
+    fn widening(&self, rhs: &Self, thresholds: &HashSet<i64>) -> Self {
+        let (lhs_dbm, rhs_dbm) = match (&self.dbm, &rhs.dbm) {
+            (None, _) => return rhs.clone(),
+            (_, None) => return self.clone(),
+            (Some(lhs), Some(rhs)) => (lhs, rhs),
+        };
+
+        let thresholds: Vec<i64> = thresholds
+            .iter()
+            .flat_map(|t| [Some(*t), t.checked_neg()])
+            .flatten()
+            .collect();
+        let widened = lhs_dbm.widening(rhs_dbm, |_, _, bound| {
+            thresholds
+                .iter()
+                .map(|t| Int::Num(*t))
+                .filter(|t| *t >= bound)
+                .min()
+                .unwrap_or(Int::PosInf)
+        });
+        self.with_dbm(Some(widened))
+    }
+
+    fn narrowing(&self, rhs: &Self) -> Self {
+        match (&self.dbm, &rhs.dbm) {
+            (None, _) => rhs.clone(),
+            (_, None) => self.clone(),
+            (Some(lhs), Some(rhs)) => self.with_dbm(close(lhs.narrowing(rhs))),
+        }
+    }
+
+    fn value_of(&self, var: &str) -> Interval {
+        let Some(dbm) = &self.dbm else {
+            return Interval::bottom();
+        };
+        let k = self.index(var);
+        Interval::new(-dbm.get(k, ZERO), dbm.get(ZERO, k))
+    }
+
+    fn assign(&self, var: &'a str, value: &ArithmeticExp<'a>) -> Self {
+        let Some(dbm) = &self.dbm else {
+            return Self::bottom();
+        };
+        let k = self.index(var);
+        let mut dbm = dbm.clone();
+
+        let expr = LinearExpr::from_exp(value);
+        let vars: Vec<_> = expr
+            .iter()
+            .flat_map(|expr| expr.coeffs.iter().map(|(var, a)| (*var, *a)))
+            .collect();
+        match (expr.as_ref(), &vars[..]) {
+            // x := x + c translates every constraint on x
+            (Some(expr), [(y, 1)]) if *y == var => {
+                dbm.translate(k, Int::Num(expr.constant));
+            }
+            // x := y + c
+            (Some(expr), [(y, 1)]) => {
+                let y = self.index(y);
+                dbm.forget(k);
+                dbm.tighten(y, k, Int::Num(expr.constant));
+                dbm.tighten(k, y, -Int::Num(expr.constant));
+            }
+            _ => {
+                let value = self.eval(value);
+                if value == Interval::bottom() {
+                    return Self::bottom();
+                }
+                dbm.forget(k);
+                Self::add_bounds(&mut dbm, k, value);
+            }
+        }
+        self.with_dbm(close(dbm))
+    }
+
+    fn filter(&self, cond: &ArithmeticCondition<'a>) -> Self {
+        let Some(dbm) = &self.dbm else {
+            return Self::bottom();
+        };
+
+        let Some(expr) = LinearExpr::from_exp(&cond.lhs) else {
+            return self.with_dbm(self.filter_intervals(dbm, cond));
+        };
+        if expr.coeffs.is_empty() {
+            let c = expr.constant;
+            let holds = match cond.operator {
+                ConditionOperator::Equal => c == 0,
+                ConditionOperator::NotEqual => c != 0,
+                ConditionOperator::StrictlyLess => c < 0,
+                ConditionOperator::GreaterOrEqual => c >= 0,
+            };
+            return match holds {
+                true => self.clone(),
+                false => Self::bottom(),
+            };
+        }
+
+        let mut refined = dbm.clone();
+        let difference = match cond.operator {
+            ConditionOperator::Equal => expr.scale(-1).is_some_and(|neg_expr| {
+                self.add_le_zero(&mut refined, &expr) && self.add_le_zero(&mut refined, &neg_expr)
+            }),
+            // over the integers e < 0 is e + 1 <= 0
+            ConditionOperator::StrictlyLess => expr
+                .add(&LinearExpr::constant(1))
+                .is_some_and(|expr| self.add_le_zero(&mut refined, &expr)),
+            ConditionOperator::GreaterOrEqual => expr
+                .scale(-1)
+                .is_some_and(|expr| self.add_le_zero(&mut refined, &expr)),
+            ConditionOperator::NotEqual => false,
+        };
+
+        match difference {
+            true => self.with_dbm(close(refined)),
+            false => self.with_dbm(self.filter_intervals(dbm, cond)),
+        }
+    }
+
+    fn vars(&self) -> HashSet<&'a str> {
+        self.vars.iter().copied().collect()
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use std::collections::HashSet;
+
+    use crate::{
+        abstract_domains::{abstract_domain::AbstractDomain, int::Int, interval::Interval},
+        abstract_states::{
+            abstract_state::AbstractState,
+            test_utils::{assign, build, cond},
+        },
+    };
+
+    use super::Zone;
+
+    #[test]
+    fn zone_filter() {
+        let s = build::<Zone>(&["i", "j"], &["j >= 0", "j <= 10"]);
+        let s = s.filter(&cond("i < j"));
+        assert_eq!(s.value_of("i"), Interval::new(Int::NegInf, Int::Num(9)));
+        assert_eq!(s.to_string(), "{ i := [-inf,9] j := [0,10] | i - j <= -1 }");
+
+        assert!(s.filter(&cond("j < i + 1")).is_bottom());
+        // x + y is not a difference, only the interval bounds are refined
+        let s = s.filter(&cond("i + j < 0"));
+        assert_eq!(s.value_of("i"), Interval::new(Int::NegInf, Int::Num(-1)));
+    }
+
+    #[test]
+    fn zone_assign() {
+        let s = build::<Zone>(&["x", "y"], &["x >= 0", "x <= 10"]);
+        let s = assign(&s, "y", "x + 1");
+        assert_eq!(s.to_string(), "{ x := [0,10] y := [1,11] | x - y = -1 }");
+
+        let s = assign(&s, "x", "x + 2");
+        assert_eq!(s.to_string(), "{ x := [2,12] y := [1,11] | x - y = 1 }");
+
+        let s = assign(&s, "y", "-1 * x");
+        assert_eq!(s.to_string(), "{ x := [2,12] y := [-12,-2] }");
+    }
+
+    #[test]
+    fn zone_widening_thresholds() {
+        let s = build::<Zone>(&["i", "j"], &[]);
+        let lhs = assign(&assign(&s, "i", "0"), "j", "0");
+        let rhs = lhs.lub(&assign(&assign(&s, "i", "1"), "j", "1"));
+
+        let widened = lhs.widening(&rhs, &HashSet::from([10]));
+        assert_eq!(
+            widened.to_string(),
+            "{ i := [0,10] j := [0,10] | i - j = 0 }"
+        );
+        let widened = lhs.widening(&rhs, &HashSet::new());
+        assert_eq!(
+            widened.to_string(),
+            "{ i := [0,inf] j := [0,inf] | i - j = 0 }"
+        );
+    }
+
+    #[test]
+    fn zone_overflow() {
+        let s = build::<Zone>(&["x", "y"], &["x >= -9223372036854775807 - 1", "x <= 0"]);
+        assert_eq!(s.value_of("x"), Interval::new(Int::NegInf, Int::Num(0)));
+
+        let s = build::<Zone>(&["x", "y"], &["x >= 0", "x <= 10"]);
+        let s = assign(&s, "y", "x - 9223372036854775807 - 1");
+        let s = assign(&s, "x", "x - 9223372036854775807 - 1");
+        let min = Interval::new(Int::Num(i64::MIN), Int::Num(i64::MIN + 10));
+        assert_eq!(s.value_of("x").lub(&min), s.value_of("x"));
+        assert_eq!(s.value_of("y").lub(&min), s.value_of("y"));
+
+        let widened = s.widening(&assign(&s, "x", "x + 1"), &HashSet::from([i64::MIN]));
+        assert!(!widened.is_bottom());
+    }
+}
@@ -0,0 +1,196 @@
+use lalrpop_util::{ErrorRecovery, ParseError};
+
+use crate::{
+    grammar::StatementParser,
+    parser::{
+        ast::Statement,
+        lexer::Lexer,
+        tokens::{LexicalError, Token},
+    },
+};
+
+type Error<'input> = ParseError<usize, Token<'input>, LexicalError>;
+
+// an error of the program with the byte range of the source it refers to
+#[derive(Clone, Debug, PartialEq)]
+pub struct Diagnostic {
+    pub message: String,
+    pub start: usize,
+    pub end: usize,
+}
+
+impl Diagnostic {
+    fn new(message: String, start: usize, end: usize) -> Self {
+        Diagnostic {
+            message,
+            start,
+            end,
+        }
+    }
+
+    fn from_parse_error(error: Error, source: &str) -> Self {
+        match error {
+            ParseError::InvalidToken { location } => {
+                Self::new("invalid token".to_string(), location, location + 1)
+            }
+            ParseError::UnrecognizedEof { location, expected } => Self::new(
+                format!("unexpected end of file{}", expectation(&expected)),
+                location,
+                location,
+            ),
+            ParseError::UnrecognizedToken {
+                token: (start, Token::Error(error), end),
+                expected: _,
+            } => Self::from_lexical_error(error, start, end, source),
+            ParseError::UnrecognizedToken {
+                token: (start, _, end),
+                expected,
+            } => Self::new(
+                format!(
+                    "unexpected `{}`{}",
+                    &source[start..end],
+                    expectation(&expected)
+                ),
+                start,
+                end,
+            ),
+            ParseError::ExtraToken {
+                token: (start, _, end),
+            } => Self::new(
+                format!("unexpected `{}` after the program", &source[start..end]),
+                start,
+                end,
+            ),
+            ParseError::User {
+                error: error @ LexicalError::EmptyRandom { start, end, .. },
+            } => Self::new(error.to_string(), start, end),
+            ParseError::User { error } => Self::new(error.to_string(), 0, 0),
+        }
+    }
+
+    fn from_lexical_error(error: LexicalError, start: usize, end: usize, source: &str) -> Self {
+        let message = match error {
+            LexicalError::InvalidToken => format!("unknown character `{}`", &source[start..end]),
+            error => error.to_string(),
+        };
+        Self::new(message, start, end)
+    }
+
+    // 1-based line and column of a byte offset
+    fn line_column(source: &str, offset: usize) -> (usize, usize) {
+        let before = &source[..offset.min(source.len())];
+        let line = before.matches('\n').count() + 1;
+        let line_start = before.rfind('\n').map_or(0, |i| i + 1);
+        (line, before[line_start..].chars().count() + 1)
+    }
+
+    // rustc-like rendering: the message, the location and the source line with the range
+    // underlined, a range spanning several lines is underlined up to the end of its first line
+    pub fn render(&self, file: &str, source: &str) -> String {
+        let (line, column) = Self::line_column(source, self.start);
+        let code = source.lines().nth(line - 1).unwrap_or_default();
+        let line_start = self.start.min(source.len()) - (column - 1);
+        let end = self.end.clamp(self.start, line_start + code.len());
+        let underline = source[self.start.min(end)..end].chars().count().max(1);
+
+        let gutter = " ".repeat(line.to_string().len());
+        format!(
+            "error: {}\n{gutter}--> {file}:{line}:{column}\n{gutter} |\n{line} | {code}\n{gutter} | {}{}",
+            self.message,
+            " ".repeat(column - 1),
+            "^".repeat(underline)
+        )
+    }
+}
+
+fn expectation(expected: &[String]) -> String {
+    match expected {
+        [] => String::new(),
+        [token] => format!(", expected {token}"),
+        _ => format!(", expected one of {}", expected.join(", ")),
+    }
+}
+
+// the program, or every error found while recovering from them
+pub fn parse(source: &str) -> Result<Box<Statement<'_>>, Vec<Diagnostic>> {
+    let mut recovered: Vec<ErrorRecovery<usize, Token, LexicalError>> = vec![];
+    let result = StatementParser::new().parse(source, &mut recovered, Lexer::new(source));
+
+    let mut diagnostics = vec![];
+    for ErrorRecovery {
+        error,
+        dropped_tokens,
+    } in recovered
+    {
+        let diagnostic = Diagnostic::from_parse_error(error, source);
+        // the lexical errors among the skipped tokens are errors on their own, the skipped
+        // tokens start with the unexpected one, which is already reported
+        let skipped: Vec<_> = dropped_tokens
+            .into_iter()
+            .filter_map(|(start, token, end)| match token {
+                Token::Error(error) if start != diagnostic.start => {
+                    Some(Diagnostic::from_lexical_error(error, start, end, source))
+                }
+                _ => None,
+            })
+            .collect();
+        diagnostics.push(diagnostic);
+        diagnostics.extend(skipped);
+    }
+
+    match result {
+        Ok(program) if diagnostics.is_empty() => Ok(program),
+        Ok(_) => Err(diagnostics),
+        Err(error) => {
+            diagnostics.push(Diagnostic::from_parse_error(error, source));
+            Err(diagnostics)
+        }
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use super::{Diagnostic, parse};
+
+    #[test]
+    fn diagnostics_recovery() {
+        let src = "x := 1 +;\ny := 99999999999999999999;\nz := 2 @ 3";
+        let errors = parse(src).unwrap_err();
+        assert_eq!(errors.len(), 3);
+        assert!(errors[0].message.starts_with("unexpected `;`"));
+        assert!(errors[1].message.starts_with("invalid integer literal"));
+        assert_eq!(errors[2].message, "unknown character `@`");
+        assert!(parse("x := 1; y := x").is_ok());
+    }
+
+    #[test]
+    fn diagnostics_empty_random() {
+        let src = "x := 1;
+y := rand(5, 1) + x";
+        let errors = parse(src).unwrap_err();
+        assert_eq!(
+            errors[0].render("a.toy", src),
+            "error: empty range in rand(5, 1), the lower bound is greater than the upper bound\n --> a.toy:2:6\n  |\n2 | y := rand(5, 1) + x\n  |      ^^^^^^^^^^"
+        );
+        assert!(parse("y := rand(1, 1)").is_ok());
+    }
+
+    #[test]
+    fn diagnostics_render() {
+        let src = "x := 1;\nif x < then skip else skip";
+        let errors = parse(src).unwrap_err();
+        assert_eq!(
+            errors[0].render("a.toy", src),
+            "error: unexpected `then`, expected one of \"identifier\", \"int\", \"rand\", \"?\", \"(\", \"-\"\n --> a.toy:2:8\n  |\n2 | if x < then skip else skip\n  |        ^^^^"
+        );
+        let eof = Diagnostic {
+            message: "unexpected end of file".to_string(),
+            start: src.len(),
+            end: src.len(),
+        };
+        assert!(
+            eof.render("a.toy", src)
+                .ends_with("2 | if x < then skip else skip\n  |                           ^")
+        );
+    }
+}
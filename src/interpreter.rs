@@ -1,51 +1,303 @@
 use std::{
     collections::{BTreeMap, HashMap, HashSet},
-    env, usize,
+    fmt,
 };
 
 use crate::{
-    abstract_domains::abstract_domain::AbstractDomain,
-    parser::ast::{ArithmeticExp, Assignment, BooleanExp, Operator, Position, Statement},
-    propagation_algo::propagation_algo::PropagationAlgorithm,
-    state::State,
+    abstract_domains::abstract_domain::{AbstractDomain, IntervalBound},
+    abstract_states::{abstract_state::AbstractState, partitioned::Choice},
+    parser::ast::{
+        ArithmeticCondition, ArithmeticExp, Assignment, BooleanExp, Comparison, Operator, Position,
+        Span, Statement,
+    },
 };
 
-pub type Invariant<'a, D> = State<'a, D>;
+pub type ProgramInvariants<S> = BTreeMap<Position, S>;
 
-pub type ProgramInvariants<'a, D> = BTreeMap<Position, Invariant<'a, D>>;
+// outcome of an assertion over the states reaching it
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub enum Verdict {
+    Proven,
+    PossiblyViolated,
+    DefinitelyViolated,
+}
+
+impl Verdict {
+    fn classify<'a, S: AbstractState<'a>>(guard: &BooleanExp<'a>, state: &S) -> Self {
+        let violated = Interpreter::<S>::bexp_eval(&!guard.clone(), state);
+        let holds = Interpreter::<S>::bexp_eval(guard, state);
+        match (violated.is_bottom(), holds.is_bottom()) {
+            (true, _) => Verdict::Proven,
+            (false, true) => Verdict::DefinitelyViolated,
+            (false, false) => Verdict::PossiblyViolated,
+        }
+    }
+
+    // an assertion evaluated several times, like in a loop body, is checked against every state
+    fn join(self, other: Self) -> Self {
+        match (self, other) {
+            _ if self == other => self,
+            _ => Verdict::PossiblyViolated,
+        }
+    }
+}
+
+impl fmt::Display for Verdict {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            Verdict::Proven => write!(f, "proven"),
+            Verdict::PossiblyViolated => write!(f, "possibly violated"),
+            Verdict::DefinitelyViolated => write!(f, "definitely violated"),
+        }
+    }
+}
+
+// an assertion reached by the analysis, with its verdict over all the states that reached it
+#[derive(Clone, Debug)]
+pub struct Assertion {
+    pub span: Span,
+    pub verdict: Verdict,
+}
+
+pub type Assertions = BTreeMap<Position, Assertion>;
+
+// the division sites reached by the analysis, each one with the verdict of the implicit
+// assertion that its divisor is not zero, indexed by the start of the divisor
+pub type Divisions = BTreeMap<Position, Assertion>;
+
+// the operations that may not fit in the machine integers, each one with the verdict of the
+// implicit assertion that its result fits, indexed by its start and end
+pub type Overflows = BTreeMap<(Position, Position), Assertion>;
+
+// whether a guard reached by the analysis held, and failed, in some of the states reaching it
+#[derive(Clone, Copy, Debug, Default)]
+pub struct Guard {
+    pub holds: bool,
+    pub fails: bool,
+}
+
+// indexed by the start of the guard
+pub type Guards = BTreeMap<Position, Guard>;
+
+// the program points where the state is reported besides the loop heads
+#[derive(Clone, Copy, Debug, PartialEq, Eq, PartialOrd, Ord)]
+pub enum Point {
+    // entry of a branch of a conditional, at the start of the branch
+    Then,
+    Else,
+    // after a statement, at its end
+    After,
+    // after a loop, at its end
+    LoopExit,
+}
+
+pub type Annotations<S> = BTreeMap<(Position, Point), S>;
+
+// the work done to analyze a loop, summed over all the times it was analyzed, like for a loop
+// nested in another one
+#[derive(Clone, Copy, Debug, Default, PartialEq)]
+pub struct LoopIterations {
+    pub analyses: usize,
+    pub widening: usize,
+    pub narrowing: usize,
+}
+
+// where traces are kept apart and where they are merged again, points are identified by the
+// line of their if/while keyword
+#[derive(Clone, Debug, Default)]
+pub struct Partitioning {
+    pub all_conditionals: bool,
+    pub conditionals: HashSet<usize>,
+    // unrolling applied to every loop, unless overridden for a specific one
+    pub unroll: usize,
+    pub unrolled_loops: HashMap<usize, usize>,
+    pub merges: HashSet<usize>,
+    pub merge_after_loops: bool,
+}
+
+impl Partitioning {
+    pub fn is_enabled(&self) -> bool {
+        self.all_conditionals
+            || !self.conditionals.is_empty()
+            || self.unroll > 0
+            || self.unrolled_loops.values().any(|n| *n > 0)
+    }
+
+    fn partitions(&self, pos: &Position) -> bool {
+        self.all_conditionals || self.conditionals.contains(&pos.line)
+    }
+
+    fn unrolling(&self, pos: &Position) -> usize {
+        *self.unrolled_loops.get(&pos.line).unwrap_or(&self.unroll)
+    }
+
+    fn merge<'a, S: AbstractState<'a>>(&self, pos: &Position, state: &S) -> S {
+        match self.merges.contains(&pos.line) {
+            true => state.merge_partitions(),
+            false => state.clone(),
+        }
+    }
+}
+
+// the machine integers the program computes with, the mathematical integers when there are none
+#[derive(Clone, Copy, Debug, PartialEq)]
+pub enum IntegerType {
+    I64,
+    I32,
+    U32,
+}
+
+// what an operation whose result does not fit does
+#[derive(Clone, Copy, Debug, PartialEq)]
+pub enum OverflowBehavior {
+    // the result is taken modulo 2^bits
+    Wrap,
+    // the execution stops
+    Trap,
+}
+
+#[derive(Clone, Copy, Debug, PartialEq)]
+pub struct Machine {
+    pub integers: IntegerType,
+    pub overflow: OverflowBehavior,
+}
+
+impl Machine {
+    fn min(&self) -> i64 {
+        match self.integers {
+            IntegerType::I64 => i64::MIN,
+            IntegerType::I32 => i32::MIN.into(),
+            IntegerType::U32 => 0,
+        }
+    }
 
-pub struct Interpreter<'a, D: AbstractDomain> {
+    fn max(&self) -> i64 {
+        match self.integers {
+            IntegerType::I64 => i64::MAX,
+            IntegerType::I32 => i32::MAX.into(),
+            IntegerType::U32 => u32::MAX.into(),
+        }
+    }
+
+    // 2^bits when it is an i64
+    fn modulus(&self) -> Option<i64> {
+        match self.integers {
+            IntegerType::I64 => None,
+            IntegerType::I32 | IntegerType::U32 => Some(1 << 32),
+        }
+    }
+
+    // whether the integers of [low, upper] fit
+    fn verdict(&self, low: i64, upper: i64) -> Verdict {
+        match (low, upper) {
+            _ if self.min() <= low && upper <= self.max() => Verdict::Proven,
+            _ if upper < self.min() || self.max() < low => Verdict::DefinitelyViolated,
+            _ => Verdict::PossiblyViolated,
+        }
+    }
+
+    fn range<D: AbstractDomain>(&self) -> D {
+        within(self.min(), self.max())
+    }
+
+    // whether every concrete value of value is in [low, upper], the order of D only tells it
+    // when D keeps apart the values of [low, upper] from the other ones, like the intervals do.
+    // The values beyond the i64 are the infinite bounds
+    fn contains<D: AbstractDomain>(low: i64, upper: i64, value: &D) -> bool {
+        let range: D = within(low, upper);
+        let below = match low.checked_sub(1) {
+            Some(below) => {
+                D::interval_abstraction(IntervalBound::NegInf, IntervalBound::Num(below))
+            }
+            None => D::top(),
+        };
+        let above = match upper.checked_add(1) {
+            Some(above) => {
+                D::interval_abstraction(IntervalBound::Num(above), IntervalBound::PosInf)
+            }
+            None => D::top(),
+        };
+        let exact = range != D::top()
+            && (low == i64::MIN || range.glb(&below) == D::bottom())
+            && (upper == i64::MAX || range.glb(&above) == D::bottom());
+        exact && value.lub(&range) == range
+    }
+
+    // exp minus the multiple of 2^bits that brings all its values in the range when there is one,
+    // any value of the range otherwise, only the results of a few wrap-arounds are looked for
+    fn wrap<'a, D: AbstractDomain>(&self, exp: ArithmeticExp<'a>, value: &D) -> ArithmeticExp<'a> {
+        let span = exp.span().clone();
+        if let (ArithmeticExp::Integer(c, _), Some(modulus)) = (&exp, self.modulus()) {
+            let wrapped = (i128::from(*c) - i128::from(self.min())).rem_euclid(i128::from(modulus));
+            return ArithmeticExp::Integer(self.min() + wrapped as i64, span);
+        }
+        if let Some(modulus) = self.modulus() {
+            for k in [-1, 1, -2, 2, -3, 3, -4, 4] {
+                let shift = k * modulus;
+                if Self::contains(self.min() + shift, self.max() + shift, value) {
+                    let span = span.desugared();
+                    return ArithmeticExp::BinaryOperation {
+                        lhs: Box::new(exp),
+                        operator: Operator::Sub,
+                        rhs: Box::new(ArithmeticExp::Integer(shift, span.clone())),
+                        span,
+                    };
+                }
+            }
+        }
+        self.any(span)
+    }
+
+    // any machine integer, the domains have no other integers than the i64 but their infinite
+    // bounds
+    fn any<'a>(&self, span: Span) -> ArithmeticExp<'a> {
+        match self.integers {
+            IntegerType::I64 => ArithmeticExp::Unknown(span),
+            IntegerType::I32 | IntegerType::U32 => ArithmeticExp::Random {
+                low: self.min(),
+                upper: self.max(),
+                span,
+            },
+        }
+    }
+}
+
+fn within<D: AbstractDomain>(low: i64, upper: i64) -> D {
+    D::interval_abstraction(IntervalBound::Num(low), IntervalBound::Num(upper))
+}
+
+pub struct Interpreter<'a, S: AbstractState<'a>> {
     program: &'a Statement<'a>,
-    initial_state: State<'a, D>,
+    initial_state: S,
     widening_thresholds: HashSet<i64>,
     narrowing_steps: usize,
-    invariants: ProgramInvariants<'a, D>,
+    partitioning: Partitioning,
+    invariants: ProgramInvariants<S>,
+    assertions: Assertions,
+    divisions: Divisions,
+    machine: Option<Machine>,
+    overflows: Overflows,
+    guards: Guards,
+    // the starts of the statements reached by some state that is not bottom
+    reached: HashSet<Position>,
+    annotations: Annotations<S>,
+    loops: BTreeMap<Position, LoopIterations>,
+    // whether the states reached are annotated
+    annotate: bool,
+    // whether the states reached are reported, never while seeking a loop invariant since the
+    // iterates are not states of the program, only the invariant found is
+    recording: bool,
 }
 
-impl<'a, D: AbstractDomain> Interpreter<'a, D> {
-    pub fn build(
-        program: &'a Statement<'a>,
-        given_vars: HashMap<&'a str, &str>,
-    ) -> Interpreter<'a, D> {
-        D::init();
-        let narrowing_steps = env::var("NARROWING_STEPS")
-            .unwrap_or("0".to_string())
-            .parse()
-            .unwrap_or(0_usize);
-        println!("Narrowing steps: {narrowing_steps}");
-
+impl<'a, S: AbstractState<'a>> Interpreter<'a, S> {
+    pub fn build(program: &'a Statement<'a>, ctx: &S::Context) -> Interpreter<'a, S> {
         let mut consts = HashSet::new();
         program.extract_constant(&mut consts);
         println!("Constants in the program: {:#?}", &consts);
 
         let mut vars = HashSet::new();
         program.extract_vars(&mut vars);
-        let mut vars: HashMap<&'a str, D> = vars.into_iter().map(|var| (var, D::top())).collect();
-        given_vars.iter().for_each(|(var, value)| {
-            vars.insert(var, D::try_from(value).unwrap_or(D::top()));
-        });
-
-        let initial_state = State::new(vars);
+        let initial_state = S::build(vars, ctx);
         println!("Initial state {initial_state}");
 
         Interpreter {
@@ -53,11 +305,52 @@ impl<'a, D: AbstractDomain> Interpreter<'a, D> {
             widening_thresholds: consts,
             invariants: BTreeMap::new(),
             initial_state,
-            narrowing_steps,
+            narrowing_steps: 0,
+            partitioning: Partitioning::default(),
+            assertions: BTreeMap::new(),
+            divisions: BTreeMap::new(),
+            machine: None,
+            overflows: BTreeMap::new(),
+            guards: BTreeMap::new(),
+            reached: HashSet::new(),
+            annotations: BTreeMap::new(),
+            loops: BTreeMap::new(),
+            annotate: false,
+            recording: true,
+        }
+    }
+
+    // the decreasing iterations run after the widening to refine each loop invariant
+    pub fn with_narrowing_steps(mut self, narrowing_steps: usize) -> Self {
+        self.narrowing_steps = narrowing_steps;
+        self
+    }
+
+    pub fn with_partitioning(mut self, partitioning: Partitioning) -> Self {
+        self.partitioning = partitioning;
+        self
+    }
+
+    // the operations compute on machine integers and may overflow
+    pub fn with_machine(mut self, machine: Option<Machine>) -> Self {
+        self.machine = machine;
+        // the variables start with any machine integer
+        if let Some(machine) = machine.filter(|machine| machine.integers != IntegerType::I64) {
+            for var in self.initial_state.vars() {
+                let any = machine.any(Span::default());
+                self.initial_state = self.initial_state.assign(var, &any);
+            }
         }
+        self
     }
 
-    pub fn interpret(&mut self) -> ProgramInvariants<'a, D> {
+    // annotates every program point and not only the loop heads
+    pub fn with_annotations(mut self, annotate: bool) -> Self {
+        self.annotate = annotate;
+        self
+    }
+
+    pub fn interpret(&mut self) -> ProgramInvariants<S> {
         let program = self.program;
         let initial_state = self.initial_state.clone();
         let last_state = self.statement_eval(program, &initial_state);
@@ -71,47 +364,279 @@ impl<'a, D: AbstractDomain> Interpreter<'a, D> {
         self.invariants.clone()
     }
 
-    pub fn aexp_eval(exp: &ArithmeticExp<'a>, state: &State<'a, D>) -> D {
-        match exp {
-            ArithmeticExp::Variable(var) => state.lookup(var).clone(),
-            ArithmeticExp::Integer(x) => D::constant_abstraction(*x),
-            ArithmeticExp::BinaryOperation { lhs, operator, rhs } => {
-                let lhs_value = Self::aexp_eval(lhs, state);
-                let rhs_value = Self::aexp_eval(rhs, state);
-                match operator {
-                    Operator::Add => lhs_value + rhs_value,
-                    Operator::Sub => lhs_value - rhs_value,
-                    Operator::Mul => lhs_value * rhs_value,
-                    Operator::Div => lhs_value / rhs_value,
+    // the assertions reached by the analysis, the other ones are dead code
+    pub fn assertions(&self) -> &Assertions {
+        &self.assertions
+    }
+
+    pub fn divisions(&self) -> &Divisions {
+        &self.divisions
+    }
+
+    pub fn overflows(&self) -> &Overflows {
+        &self.overflows
+    }
+
+    pub fn guards(&self) -> &Guards {
+        &self.guards
+    }
+
+    pub fn program(&self) -> &'a Statement<'a> {
+        self.program
+    }
+
+    // the statements never reached are dead code
+    pub fn reached(&self, stmt: &Statement<'a>) -> bool {
+        self.reached.contains(&stmt.span().start)
+    }
+
+    // the states of every program point, joined over all the times it was reached
+    pub fn annotations(&self) -> &Annotations<S> {
+        &self.annotations
+    }
+
+    pub fn loop_iterations(&self) -> &BTreeMap<Position, LoopIterations> {
+        &self.loops
+    }
+
+    pub fn narrowing_steps(&self) -> usize {
+        self.narrowing_steps
+    }
+
+    pub fn widening_thresholds(&self) -> &HashSet<i64> {
+        &self.widening_thresholds
+    }
+
+    fn annotate(&mut self, pos: &Position, point: Point, state: &S) {
+        if !self.annotate || !self.recording {
+            return;
+        }
+        self.annotations
+            .entry((pos.clone(), point))
+            .and_modify(|s| *s = s.lub(state))
+            .or_insert_with(|| state.clone());
+    }
+
+    // the expression computing exp on the machine integers, whose values are the ones of exp
+    // with the mathematical integers, None when every trace traps. Every division in exp is
+    // checked against a zero divisor and every operation against an overflow, like the assertions
+    // they are checked against every state they are evaluated in. The constants and inputs are
+    // converted to the machine integers unless they are compared
+    fn aexp_eval(
+        &mut self,
+        exp: &ArithmeticExp<'a>,
+        state: &S,
+        compared: bool,
+    ) -> Option<ArithmeticExp<'a>> {
+        if state.is_bottom() {
+            return Some(exp.clone());
+        }
+        let machine_exp = match exp {
+            ArithmeticExp::BinaryOperation {
+                lhs,
+                operator,
+                rhs,
+                span,
+            } => {
+                let machine_lhs = self.aexp_eval(lhs, state, compared)?;
+                let machine_rhs = self.aexp_eval(rhs, state, compared)?;
+                if let Operator::Div | Operator::Mod = operator {
+                    self.check_division(span, rhs.span(), state.eval(&machine_rhs));
+                }
+                let machine_exp = ArithmeticExp::BinaryOperation {
+                    lhs: Box::new(machine_lhs),
+                    operator: *operator,
+                    rhs: Box::new(machine_rhs),
+                    span: span.clone(),
+                };
+                // the difference of the sides of a comparison and the offset added by its normal
+                // form are no operations
+                if span.desugared {
+                    return Some(machine_exp);
                 }
+                machine_exp
             }
+            ArithmeticExp::Negation(operand, span) => {
+                let operand = self.aexp_eval(operand, state, compared)?;
+                ArithmeticExp::Negation(Box::new(operand), span.clone())
+            }
+            ArithmeticExp::Unknown(span) => match self.machine {
+                Some(machine) if !compared => machine.any(span.clone()),
+                _ => return Some(exp.clone()),
+            },
+            ArithmeticExp::Integer(..) | ArithmeticExp::Random { .. } if !compared => exp.clone(),
+            _ => return Some(exp.clone()),
+        };
+        self.check_overflow(machine_exp, state)
+    }
+
+    fn check_division(&mut self, span: &Span, divisor_span: &Span, divisor: S::Value) {
+        if !self.recording {
+            return;
+        }
+        let zero = S::Value::constant_abstraction(0);
+        let verdict = match divisor {
+            _ if divisor == S::Value::bottom() => return,
+            _ if divisor.glb(&zero) == S::Value::bottom() => Verdict::Proven,
+            _ if divisor.lub(&zero) == zero => Verdict::DefinitelyViolated,
+            _ => Verdict::PossiblyViolated,
+        };
+        self.divisions
+            .entry(divisor_span.start.clone())
+            .and_modify(|a| a.verdict = a.verdict.join(verdict))
+            .or_insert_with(|| Assertion {
+                span: span.clone(),
+                verdict,
+            });
+    }
+
+    // the constants and inputs are only reported when they do not fit
+    fn check_overflow(&mut self, exp: ArithmeticExp<'a>, state: &S) -> Option<ArithmeticExp<'a>> {
+        let Some(machine) = self.machine else {
+            return Some(exp);
+        };
+        let value = state.eval(&exp);
+        let verdict = match exp {
+            _ if value == S::Value::bottom() => return Some(exp),
+            // the constants and inputs do not depend on the domain
+            ArithmeticExp::Integer(c, _) => machine.verdict(c, c),
+            ArithmeticExp::Random { low, upper, .. } => machine.verdict(low, upper),
+            ArithmeticExp::Unknown(_) => Verdict::Proven,
+            _ if Machine::contains(machine.min(), machine.max(), &value) => Verdict::Proven,
+            _ if value.glb(&machine.range()) == S::Value::bottom() => Verdict::DefinitelyViolated,
+            _ => Verdict::PossiblyViolated,
+        };
+        let operation = matches!(
+            exp,
+            ArithmeticExp::BinaryOperation { .. } | ArithmeticExp::Negation(..)
+        );
+        if self.recording && (operation || verdict != Verdict::Proven) {
+            let span = exp.span();
+            self.overflows
+                .entry((span.start.clone(), span.end.clone()))
+                .and_modify(|a| a.verdict = a.verdict.join(verdict))
+                .or_insert_with(|| Assertion {
+                    span: span.clone(),
+                    verdict,
+                });
+        }
+
+        match (verdict, machine.overflow) {
+            (Verdict::Proven, _) => Some(exp),
+            (Verdict::DefinitelyViolated, OverflowBehavior::Trap) => None,
+            // the traces that trap stop there, the other ones compute exp
+            (_, OverflowBehavior::Trap) => Some(exp),
+            (_, OverflowBehavior::Wrap) => Some(machine.wrap(exp, &value)),
         }
     }
 
-    fn bexp_eval(exp: &BooleanExp<'a>, state: &State<'a, D>) -> State<'a, D> {
+    // the guard computing exp on the machine integers, None when every trace traps. The
+    // operands of & and | are evaluated from left to right, the right one only when the left one
+    // does not decide the guard, it is false when every trace evaluating it traps
+    fn bexp_machine(&mut self, exp: &BooleanExp<'a>, state: &S) -> Option<BooleanExp<'a>> {
         match exp {
-            BooleanExp::Boolean(true) => state.clone(),
-            BooleanExp::Boolean(false) => State::bottom(),
+            BooleanExp::Boolean(..) => Some(exp.clone()),
             BooleanExp::ArithmeticCondition(cond) => {
-                let algo = PropagationAlgorithm::build(cond, state);
-                algo.local_iterations()
+                let lhs = self.aexp_eval(&cond.lhs, state, true)?;
+                Some(BooleanExp::ArithmeticCondition(ArithmeticCondition {
+                    lhs: Box::new(lhs),
+                    operator: cond.operator,
+                    span: cond.span.clone(),
+                }))
+            }
+            BooleanExp::And { lhs, rhs, span } => {
+                let lhs = self.bexp_machine(lhs, state)?;
+                let rhs = self
+                    .bexp_machine(rhs, &Self::bexp_eval(&lhs, state))
+                    .unwrap_or(BooleanExp::Boolean(false, rhs.span().clone()));
+                Some(BooleanExp::And {
+                    lhs: Box::new(lhs),
+                    rhs: Box::new(rhs),
+                    span: span.clone(),
+                })
+            }
+            BooleanExp::Or { lhs, rhs, span } => {
+                let lhs = self.bexp_machine(lhs, state)?;
+                let rhs = self
+                    .bexp_machine(rhs, &Self::bexp_eval(&!lhs.clone(), state))
+                    .unwrap_or(BooleanExp::Boolean(false, rhs.span().clone()));
+                Some(BooleanExp::Or {
+                    lhs: Box::new(lhs),
+                    rhs: Box::new(rhs),
+                    span: span.clone(),
+                })
             }
-            BooleanExp::And { lhs, rhs } => {
+        }
+    }
+
+    // the traces where var does not fit trapped, like for the initial state the i64 are left alone
+    fn trapped(&self, var: &'a str, state: &S) -> S {
+        let Some(machine) = self.machine else {
+            return state.clone();
+        };
+        if machine.overflow == OverflowBehavior::Wrap || machine.integers == IntegerType::I64 {
+            return state.clone();
+        }
+        let var = || Box::new(ArithmeticExp::Variable(var, Span::default()));
+        let bound = |c| Box::new(ArithmeticExp::Integer(c, Span::default()));
+        let fits = [
+            (Comparison::GreaterOrEqual, machine.min()),
+            (Comparison::LessOrEqual, machine.max()),
+        ];
+        fits.into_iter()
+            .fold(state.clone(), |state, (comparison, c)| {
+                match state.is_bottom() {
+                    true => state,
+                    false => state.filter(&ArithmeticCondition::normal_form(
+                        var(),
+                        comparison,
+                        bound(c),
+                    )),
+                }
+            })
+    }
+
+    // the states where the guard holds and where it does not
+    fn guard_eval(&mut self, guard: &BooleanExp<'a>, state: &S) -> (S, S) {
+        if state.is_bottom() {
+            return (S::bottom(), S::bottom());
+        }
+        let (holds, fails) = match self.bexp_machine(guard, state) {
+            Some(guard) => (
+                Self::bexp_eval(&guard, state),
+                Self::bexp_eval(&!guard, state),
+            ),
+            None => (S::bottom(), S::bottom()),
+        };
+        if self.recording {
+            let outcomes = self.guards.entry(guard.span().start.clone()).or_default();
+            outcomes.holds |= !holds.is_bottom();
+            outcomes.fails |= !fails.is_bottom();
+        }
+        (holds, fails)
+    }
+
+    fn bexp_eval(exp: &BooleanExp<'a>, state: &S) -> S {
+        match exp {
+            BooleanExp::Boolean(true, _) => state.clone(),
+            BooleanExp::Boolean(false, _) => S::bottom(),
+            BooleanExp::ArithmeticCondition(cond) => state.filter(cond),
+            BooleanExp::And { lhs, rhs, span: _ } => {
                 let mut fixpoint = false;
                 let mut x = state.clone();
                 while !fixpoint {
-                    let current = Self::bexp_eval(lhs, &x).glb_var_wise(&Self::bexp_eval(rhs, &x));
-                    fixpoint = current == x || current == State::bottom();
+                    let current = Self::bexp_eval(lhs, &x).glb(&Self::bexp_eval(rhs, &x));
+                    fixpoint = current == x || current.is_bottom();
                     x = current;
                 }
                 x
             }
-            BooleanExp::Or { lhs, rhs } => {
+            BooleanExp::Or { lhs, rhs, span: _ } => {
                 let mut fixpoint = false;
                 let mut x = state.clone();
                 while !fixpoint {
-                    let current = Self::bexp_eval(lhs, &x).lub_var_wise(&Self::bexp_eval(rhs, &x));
-                    fixpoint = current == x || current == State::bottom();
+                    let current = Self::bexp_eval(lhs, &x).lub(&Self::bexp_eval(rhs, &x));
+                    fixpoint = current == x || current.is_bottom();
                     x = current;
                 }
                 x
@@ -119,49 +644,110 @@ impl<'a, D: AbstractDomain> Interpreter<'a, D> {
         }
     }
 
-    fn statement_eval(&mut self, stmt: &Statement<'a>, state: &State<'a, D>) -> State<'a, D> {
-        if *state == State::bottom() {
-            return State::bottom();
+    fn statement_eval(&mut self, stmt: &Statement<'a>, state: &S) -> S {
+        if state.is_bottom() {
+            return S::bottom();
+        }
+        if self.recording {
+            self.reached.insert(stmt.span().start.clone());
+        }
+        let after = self.statement_semantic(stmt, state);
+        match stmt {
+            Statement::Composition { .. } => (),
+            Statement::While { span, .. } => self.annotate(&span.end, Point::LoopExit, &after),
+            _ => self.annotate(&stmt.span().end, Point::After, &after),
         }
+        after
+    }
+
+    fn statement_semantic(&mut self, stmt: &Statement<'a>, state: &S) -> S {
         match stmt {
-            Statement::Skip => state.clone(),
-            Statement::Assignment(Assignment { var, value }) => {
-                let mut updated_state = state.clone();
-                updated_state.update(&var, Self::aexp_eval(value, state));
-                updated_state
+            Statement::Skip(_) => state.clone(),
+            Statement::Assignment(Assignment {
+                var,
+                value,
+                span: _,
+            }) => match self.aexp_eval(value, state, false) {
+                Some(value) => self.trapped(var, &state.assign(var, &value)),
+                None => S::bottom(),
+            },
+            Statement::Assume { guard, span: _ } => self.guard_eval(guard, state).0,
+            Statement::Assert { guard, span } => {
+                let Some(guard) = self.bexp_machine(guard, state) else {
+                    return S::bottom();
+                };
+                if self.recording {
+                    let verdict = Verdict::classify(&guard, state);
+                    self.assertions
+                        .entry(span.start.clone())
+                        .and_modify(|a| a.verdict = a.verdict.join(verdict))
+                        .or_insert_with(|| Assertion {
+                            span: span.clone(),
+                            verdict,
+                        });
+                }
+                Self::bexp_eval(&guard, state)
             }
-            Statement::Composition { lhs, rhs } => {
+            Statement::Composition { lhs, rhs, span: _ } => {
                 let state = self.statement_eval(lhs, state);
                 self.statement_eval(rhs, &state)
             }
+            Statement::Either { lhs, rhs, span: _ } => {
+                let lhs = self.statement_eval(lhs, state);
+                lhs.lub(&self.statement_eval(rhs, state))
+            }
             Statement::Conditional {
                 guard,
                 true_branch,
                 false_branch,
+                span,
             } => {
-                let t = self.statement_eval(true_branch, &Self::bexp_eval(guard, state));
-                let f =
-                    self.statement_eval(false_branch, &Self::bexp_eval(&!*guard.clone(), state));
+                let pos = &span.start;
+                let state = &self.partitioning.merge(pos, state);
+                let (then_entry, else_entry) = self.guard_eval(guard, state);
+                self.annotate(&true_branch.span().start, Point::Then, &then_entry);
+                self.annotate(&false_branch.span().start, Point::Else, &else_entry);
+                let t = self.statement_eval(true_branch, &then_entry);
+                let f = self.statement_eval(false_branch, &else_entry);
 
-                t.lub_var_wise(&f)
+                match self.partitioning.partitions(pos) {
+                    true => t
+                        .partition(pos, Choice::Then)
+                        .lub(&f.partition(pos, Choice::Else)),
+                    false => t.lub(&f),
+                }
             }
-            Statement::While { pos, guard, body } => {
+            Statement::While { guard, body, span } => {
+                let pos = &span.start;
+                let state = &self.partitioning.merge(pos, state);
+
+                // the first iterations are unrolled, each one in its own partition
+                let unroll = self.partitioning.unrolling(pos);
+                let mut unrolled_heads = S::bottom();
+                let mut unrolled_exits = S::bottom();
+                let mut entry = state.clone();
+                for k in 0..unroll {
+                    let head = entry.partition(pos, Choice::Iteration(k));
+                    let (holds, exits) = self.guard_eval(guard, &head);
+                    unrolled_heads = unrolled_heads.lub(&head);
+                    unrolled_exits = unrolled_exits.lub(&exits);
+                    entry = self.statement_eval(body, &holds);
+                }
+                let state = &match unroll {
+                    0 => entry,
+                    _ => entry.partition(pos, Choice::AtLeast(unroll)),
+                };
+
+                let recording = std::mem::replace(&mut self.recording, false);
                 let mut fixpoint = false;
                 let mut x = state.clone();
                 let mut iter = vec![];
-                let widening = D::widening_operator();
 
                 // seeking loop invariant
                 while !fixpoint {
-                    let mut next_iter_sem =
-                        state.lub_var_wise(&self.statement_eval(body, &Self::bexp_eval(guard, &x)));
-                    if widening.is_some() {
-                        next_iter_sem = x.widening(
-                            &next_iter_sem,
-                            &self.widening_thresholds,
-                            widening.as_ref().unwrap(),
-                        )
-                    }
+                    let (holds, _) = self.guard_eval(guard, &x);
+                    let next_iter_sem = state.lub(&self.statement_eval(body, &holds));
+                    let next_iter_sem = x.widening(&next_iter_sem, &self.widening_thresholds);
                     fixpoint = x == next_iter_sem;
                     iter.push(x);
                     x = next_iter_sem;
@@ -175,8 +761,9 @@ impl<'a, D: AbstractDomain> Interpreter<'a, D> {
                 fixpoint = false;
                 // refining loop invariant
                 while !fixpoint && steps < self.narrowing_steps {
-                    let body_semantic = self.statement_eval(body, &Self::bexp_eval(guard, &x));
-                    let current = x.narrowing(&state.lub_var_wise(&body_semantic));
+                    let (holds, _) = self.guard_eval(guard, &x);
+                    let body_semantic = self.statement_eval(body, &holds);
+                    let current = x.narrowing(&state.lub(&body_semantic));
                     fixpoint = current == x;
                     narrowing_iter.push(x);
                     x = current;
@@ -186,15 +773,32 @@ impl<'a, D: AbstractDomain> Interpreter<'a, D> {
                 println!("Refine loop invariant at line {} with narrowing", pos.line);
                 dbg_iterations(&narrowing_iter);
 
-                self.invariants.insert(pos.clone(), x.clone());
-                Self::bexp_eval(&!*guard.clone(), &x)
+                let iterations = self.loops.entry(pos.clone()).or_default();
+                iterations.analyses += 1;
+                iterations.widening += iter.len() - 1;
+                iterations.narrowing += steps;
+
+                // the body is reported once, from the invariant found
+                self.recording = recording;
+                if recording {
+                    let (holds, _) = self.guard_eval(guard, &x);
+                    self.statement_eval(body, &holds);
+                }
+
+                self.invariants.insert(pos.clone(), unrolled_heads.lub(&x));
+                let exit = unrolled_exits.lub(&self.guard_eval(guard, &x).1);
+                match self.partitioning.merge_after_loops {
+                    true => exit.merge_partitions(),
+                    false => exit,
+                }
             }
         }
     }
 }
 
-fn dbg_iterations<'a, D: AbstractDomain>(v: &Vec<State<'a, D>>) {
-    if v.is_empty() {
+fn dbg_iterations<'a, S: AbstractState<'a>>(v: &[S]) {
+    // the iterates of a loop left by all the unrolled iterations are all bottom
+    if v.is_empty() || v[0].is_bottom() {
         return;
     }
 
@@ -204,7 +808,7 @@ fn dbg_iterations<'a, D: AbstractDomain>(v: &Vec<State<'a, D>>) {
         .map(|var| {
             let values = v
                 .iter()
-                .map(|s| Into::<String>::into(*s.lookup(var)))
+                .map(|s| Into::<String>::into(s.value_of(var)))
                 .reduce(|acc, e| format!("{acc}\t{e}"))
                 .unwrap();
             format!("{var} -> {}", values)
@@ -214,3 +818,150 @@ fn dbg_iterations<'a, D: AbstractDomain>(v: &Vec<State<'a, D>>) {
 
     println!("{vars}");
 }
+
+#[cfg(test)]
+mod test {
+    use crate::{
+        abstract_domains::interval::{Bounds, Interval},
+        abstract_states::abstract_state::AbstractState,
+        diagnostics::parse,
+        parser::ast::{Position, Statement},
+        state::State,
+    };
+
+    use super::{IntegerType, Interpreter, Machine, OverflowBehavior, Verdict};
+
+    // a loop where y is unbounded in the widened iterates and in [0,12] once narrowed, the
+    // constants of body are products so that they are no widening thresholds
+    fn narrowed(body: &str) -> String {
+        format!("x := 0;\ny := 0;\nwhile x < 11 do {{\n  {body};\n  x := x + 2;\n  y := x\n}}")
+    }
+
+    fn interpret<'a>(
+        program: &'a Statement<'a>,
+        machine: Option<Machine>,
+    ) -> Interpreter<'a, State<'a, Interval>> {
+        let mut interpreter = Interpreter::<State<Interval>>::build(program, &Bounds::default())
+            .with_narrowing_steps(1)
+            .with_machine(machine);
+        interpreter.interpret();
+        interpreter
+    }
+
+    #[test]
+    fn assertions_on_loop_invariant() {
+        let src = narrowed("assert y < 2 * 7");
+        let program = parse(&src).unwrap();
+        let interpreter = interpret(&program, None);
+        let verdicts: Vec<_> = interpreter
+            .assertions()
+            .values()
+            .map(|a| a.verdict)
+            .collect();
+        assert_eq!(verdicts, [Verdict::Proven]);
+    }
+
+    #[test]
+    fn divisions_on_loop_invariant() {
+        let src = narrowed("z := 1 / (2 * 7 - y)");
+        let program = parse(&src).unwrap();
+        let interpreter = interpret(&program, None);
+        let verdicts: Vec<_> = interpreter
+            .divisions()
+            .values()
+            .map(|a| a.verdict)
+            .collect();
+        assert_eq!(verdicts, [Verdict::Proven]);
+    }
+
+    #[test]
+    fn reached_on_loop_invariant() {
+        let src =
+            narrowed("if y < 2 * 7 then z := 1 else z := 2;\n  while y > 2 * 7 do y := y - 1");
+        let program = parse(&src).unwrap();
+        let interpreter = interpret(&program, None);
+        // the guard of the loop, the constant guard and the one of the loop never entered
+        let guards: Vec<_> = interpreter
+            .guards()
+            .values()
+            .map(|guard| (guard.holds, guard.fails))
+            .collect();
+        assert_eq!(guards, [(true, true), (true, false), (false, true)]);
+        let reached = |line, clm| interpreter.reached.contains(&Position { line, clm });
+        assert!(reached(3, 20));
+        // the else branch and the body of the loop never entered are dead code
+        assert!(!reached(3, 32));
+        assert!(!reached(4, 21));
+    }
+
+    #[test]
+    fn overflows_on_loop_invariant() {
+        let src = narrowed("z := 2 * 7 - y");
+        let program = parse(&src).unwrap();
+        let machine = Machine {
+            integers: IntegerType::I32,
+            overflow: OverflowBehavior::Wrap,
+        };
+        let interpreter = interpret(&program, Some(machine));
+        let verdicts: Vec<_> = interpreter
+            .overflows()
+            .values()
+            .map(|a| a.verdict)
+            .collect();
+        assert_eq!(verdicts, [Verdict::Proven; 3]);
+    }
+
+    // the values of vars at the end of src, None when every trace traps
+    fn run(
+        src: &str,
+        integers: IntegerType,
+        overflow: OverflowBehavior,
+        vars: &[&str],
+    ) -> Option<Vec<Interval>> {
+        let program = parse(src).unwrap();
+        let machine = Machine { integers, overflow };
+        let mut interpreter = Interpreter::<State<Interval>>::build(&program, &Bounds::default())
+            .with_machine(Some(machine));
+        let invariants = interpreter.interpret();
+        let end = invariants.last_key_value().unwrap().1;
+        match end.is_bottom() {
+            true => None,
+            false => Some(vars.iter().map(|var| *end.lookup(var)).collect()),
+        }
+    }
+
+    #[test]
+    fn machine_integers_wrap() {
+        let (i32_min, u32_max) = (i32::MIN.into(), u32::MAX.into());
+        let src = "x := 2147483647;\ny := x + 1";
+        let wrapped = run(src, IntegerType::I32, OverflowBehavior::Wrap, &["y"]);
+        assert_eq!(wrapped, Some(vec![Interval::from([i32_min, i32_min])]));
+
+        let src = "x := rand(0, 5);\ny := x - 6";
+        let wrapped = run(src, IntegerType::U32, OverflowBehavior::Wrap, &["y"]);
+        assert_eq!(wrapped, Some(vec![Interval::from([u32_max - 5, u32_max])]));
+    }
+
+    #[test]
+    fn machine_integers_trap() {
+        let i32_max = i32::MAX.into();
+        let src = "x := rand(0, 2147483647);\ny := x + 1";
+        let fits = run(src, IntegerType::I32, OverflowBehavior::Trap, &["y"]);
+        assert_eq!(fits, Some(vec![Interval::from([1, i32_max])]));
+
+        let src = "x := rand(0, 5);\ny := x - 3";
+        let fits = run(src, IntegerType::U32, OverflowBehavior::Trap, &["y"]);
+        assert_eq!(fits, Some(vec![Interval::from([0, 2])]));
+
+        let src = "x := rand(0, 5);\ny := x - 6";
+        assert_eq!(
+            run(src, IntegerType::U32, OverflowBehavior::Trap, &["y"]),
+            None
+        );
+
+        // the offset added to y <= x by its normal form does not trap
+        let src = "x := 4294967295;\ny := 0;\nif y <= x then z := 1 else z := 2";
+        let compared = run(src, IntegerType::U32, OverflowBehavior::Trap, &["z"]);
+        assert_eq!(compared, Some(vec![Interval::from([1, 1])]));
+    }
+}
@@ -0,0 +1,154 @@
+use super::rational::{Rational, gcd};
+
+// every vector is homogeneous: index 0 holds the constant term (or the extra dimension xi
+// for generators), index i > 0 the coefficient of the i-th variable
+
+// None when the products overflow, like every computation on the coefficients
+pub fn dot(lhs: &[Rational], rhs: &[Rational]) -> Option<Rational> {
+    lhs.iter()
+        .zip(rhs.iter())
+        .try_fold(Rational::ZERO, |acc, (a, b)| {
+            acc.checked_add(a.checked_mul(*b)?)
+        })
+}
+
+// positive multiple of v made of coprime integers, it does not change the meaning of a
+// constraint or a generator but keeps the numbers small, v is kept as it is when the common
+// denominator overflows
+pub fn normalize(v: Vec<Rational>) -> Vec<Rational> {
+    let den = v.iter().try_fold(1_i128, |acc, x| {
+        (acc / gcd(acc, x.den())).checked_mul(x.den())
+    });
+    let scaled = den.and_then(|den| {
+        v.iter()
+            .map(|x| x.checked_mul(Rational::from(den)))
+            .collect::<Option<Vec<_>>>()
+    });
+    let Some(scaled) = scaled else {
+        return v;
+    };
+    let num = scaled.iter().fold(0, |acc, x| gcd(acc, x.num()));
+    match num {
+        0 | 1 => scaled,
+        _ => scaled
+            .into_iter()
+            .map(|x| Rational::from(x.num() / num))
+            .collect(),
+    }
+}
+
+// a * lhs + b * rhs
+pub fn combine(
+    a: Rational,
+    lhs: &[Rational],
+    b: Rational,
+    rhs: &[Rational],
+) -> Option<Vec<Rational>> {
+    let combined = lhs
+        .iter()
+        .zip(rhs.iter())
+        .map(|(x, y)| a.checked_mul(*x)?.checked_add(b.checked_mul(*y)?))
+        .collect::<Option<Vec<_>>>()?;
+    Some(normalize(combined))
+}
+
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub enum ConstraintKind {
+    Equality,
+    Inequality,
+}
+
+// coeffs[0] + sum coeffs[i] * x_i = 0 or >= 0
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub struct Constraint {
+    pub kind: ConstraintKind,
+    pub coeffs: Vec<Rational>,
+}
+
+impl Constraint {
+    pub fn equality(coeffs: Vec<Rational>) -> Self {
+        Constraint {
+            kind: ConstraintKind::Equality,
+            coeffs: normalize(coeffs),
+        }
+    }
+
+    pub fn inequality(coeffs: Vec<Rational>) -> Self {
+        Constraint {
+            kind: ConstraintKind::Inequality,
+            coeffs: normalize(coeffs),
+        }
+    }
+
+    // a generator on which the constraint overflows is taken as a violation
+    pub fn is_satisfied_by(&self, generator: &Generator) -> bool {
+        let Some(value) = dot(&self.coeffs, &generator.coords) else {
+            return false;
+        };
+        match (self.kind, generator.kind) {
+            (ConstraintKind::Equality, _) | (_, GeneratorKind::Line) => value.is_zero(),
+            _ => value >= Rational::ZERO,
+        }
+    }
+
+    pub fn is_saturated_by(&self, generator: &Generator) -> bool {
+        dot(&self.coeffs, &generator.coords).is_some_and(|value| value.is_zero())
+    }
+
+    // an equality is the conjunction of two opposite inequalities
+    pub fn as_inequalities(&self) -> Vec<Constraint> {
+        let ineq = Constraint::inequality(self.coeffs.clone());
+        match self.kind {
+            ConstraintKind::Inequality => vec![ineq],
+            ConstraintKind::Equality => vec![
+                Constraint::inequality(self.coeffs.iter().map(|a| -*a).collect()),
+                ineq,
+            ],
+        }
+    }
+}
+
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub enum GeneratorKind {
+    Point,
+    Ray,
+    Line,
+}
+
+// a point (xi, x) with xi > 0 stands for x / xi, rays and lines are directions with xi = 0
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub struct Generator {
+    pub kind: GeneratorKind,
+    pub coords: Vec<Rational>,
+}
+
+impl Generator {
+    pub fn point(coords: Vec<Rational>) -> Self {
+        Generator {
+            kind: GeneratorKind::Point,
+            coords: normalize(coords),
+        }
+    }
+
+    pub fn ray(coords: Vec<Rational>) -> Self {
+        Generator {
+            kind: GeneratorKind::Ray,
+            coords: normalize(coords),
+        }
+    }
+
+    pub fn line(coords: Vec<Rational>) -> Self {
+        Generator {
+            kind: GeneratorKind::Line,
+            coords: normalize(coords),
+        }
+    }
+
+    // coordinate of the variable in position i > 0, for points divided by xi
+    pub fn coord(&self, i: usize) -> Option<Rational> {
+        match self.kind {
+            GeneratorKind::Point => self.coords[i].checked_div(self.coords[0]),
+            _ => Some(self.coords[i]),
+        }
+    }
+}
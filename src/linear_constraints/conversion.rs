@@ -0,0 +1,180 @@
+use super::{
+    constraint::{combine, dot, normalize},
+    rational::Rational,
+};
+
+// minimal generators of a polyhedral cone, rays are extreme rays modulo the lines
+#[derive(Clone, Debug, Default)]
+pub struct Cone {
+    pub rays: Vec<Vec<Rational>>,
+    pub lines: Vec<Vec<Rational>>,
+}
+
+struct Ray {
+    coords: Vec<Rational>,
+    // saturated rows among the ones already processed
+    saturated: Vec<bool>,
+}
+
+fn unit(dim: usize, i: usize) -> Vec<Rational> {
+    let mut v = vec![Rational::ZERO; dim];
+    v[i] = Rational::ONE;
+    v
+}
+
+// g - (a.g / a.l) * l lies on the hyperplane a.y = 0
+fn project(g: &[Rational], l: &[Rational], a: &[Rational], al: Rational) -> Option<Vec<Rational>> {
+    combine(Rational::ONE, g, -dot(a, g)?.checked_div(al)?, l)
+}
+
+// double description method (Motzkin–Chernikova): generators of the cone
+// { y | a.y = 0 for each equality a, a.y >= 0 for each inequality a }, the rows are added one
+// at a time starting from the whole space; the same routine computes the constraints of the
+// cone spanned by some generators, since they are the generators of its dual cone; None when
+// the coefficients overflow
+pub fn conversion(
+    dim: usize,
+    equalities: &[Vec<Rational>],
+    inequalities: &[Vec<Rational>],
+) -> Option<Cone> {
+    let rows: Vec<(&Vec<Rational>, bool)> = equalities
+        .iter()
+        .map(|a| (a, true))
+        .chain(inequalities.iter().map(|a| (a, false)))
+        .collect();
+
+    let mut lines: Vec<Vec<Rational>> = (0..dim).map(|i| unit(dim, i)).collect();
+    let mut rays: Vec<Ray> = vec![];
+
+    for (idx, (a, equality)) in rows.iter().enumerate() {
+        let crossing = lines
+            .iter()
+            .map(|l| dot(a, l))
+            .collect::<Option<Vec<_>>>()?;
+        if let Some(p) = crossing.iter().position(|al| !al.is_zero()) {
+            // a line crossing the hyperplane: every other generator is moved on the hyperplane
+            // along it, then it becomes a ray on the positive side
+            let mut l = lines.swap_remove(p);
+            let mut al = crossing[p];
+            if al < Rational::ZERO {
+                l = l.iter().map(|x| -*x).collect();
+                al = -al;
+            }
+            lines = lines
+                .iter()
+                .map(|g| project(g, &l, a, al))
+                .collect::<Option<_>>()?;
+            for r in rays.iter_mut() {
+                r.coords = project(&r.coords, &l, a, al)?;
+                r.saturated[idx] = true;
+            }
+            if !equality {
+                let mut saturated = vec![false; rows.len()];
+                saturated[..idx].iter_mut().for_each(|s| *s = true);
+                rays.push(Ray {
+                    coords: normalize(l),
+                    saturated,
+                });
+            }
+            continue;
+        }
+
+        let values: Vec<Rational> = rays
+            .iter()
+            .map(|r| dot(a, &r.coords))
+            .collect::<Option<_>>()?;
+        let positive: Vec<usize> = (0..rays.len())
+            .filter(|i| values[*i] > Rational::ZERO)
+            .collect();
+        let negative: Vec<usize> = (0..rays.len())
+            .filter(|i| values[*i] < Rational::ZERO)
+            .collect();
+
+        // two rays are adjacent when no other ray saturates all the rows they both saturate
+        let pointed_dim = dim - lines.len();
+        let adjacent = |p: usize, n: usize| {
+            let common: Vec<bool> = (0..idx)
+                .map(|k| rays[p].saturated[k] && rays[n].saturated[k])
+                .collect();
+            common.iter().filter(|s| **s).count() + 2 >= pointed_dim
+                && rays.iter().enumerate().all(|(r, ray)| {
+                    r == p || r == n || (0..idx).any(|k| common[k] && !ray.saturated[k])
+                })
+        };
+
+        let mut combined = vec![];
+        for p in positive.iter() {
+            for n in negative.iter() {
+                if adjacent(*p, *n) {
+                    let coords =
+                        combine(values[*p], &rays[*n].coords, -values[*n], &rays[*p].coords)?;
+                    let mut saturated: Vec<bool> = rays[*p]
+                        .saturated
+                        .iter()
+                        .zip(rays[*n].saturated.iter())
+                        .map(|(x, y)| *x && *y)
+                        .collect();
+                    saturated[idx] = true;
+                    combined.push(Ray { coords, saturated });
+                }
+            }
+        }
+
+        rays = rays
+            .into_iter()
+            .zip(values.iter())
+            .filter_map(|(mut r, v)| match v.signum() {
+                0 => {
+                    r.saturated[idx] = true;
+                    Some(r)
+                }
+                1 if !equality => Some(r),
+                _ => None,
+            })
+            .chain(combined)
+            .collect();
+    }
+
+    Some(Cone {
+        rays: rays.into_iter().map(|r| r.coords).collect(),
+        lines,
+    })
+}
+
+#[cfg(test)]
+mod test {
+    use crate::linear_constraints::rational::Rational;
+
+    use super::conversion;
+
+    fn v(coords: &[i64]) -> Vec<Rational> {
+        coords.iter().map(|x| Rational::from(*x)).collect()
+    }
+
+    #[test]
+    fn conversion_quadrant() {
+        let cone = conversion(2, &[], &[v(&[1, 0]), v(&[0, 1])]).unwrap();
+        assert!(cone.lines.is_empty());
+        assert_eq!(cone.rays.len(), 2);
+        assert!(cone.rays.contains(&v(&[1, 0])));
+        assert!(cone.rays.contains(&v(&[0, 1])));
+    }
+
+    #[test]
+    fn conversion_redundant_rows() {
+        // y >= x, y >= -x and the redundant y >= 0
+        let cone = conversion(2, &[], &[v(&[-1, 1]), v(&[1, 1]), v(&[0, 1])]).unwrap();
+        assert!(cone.lines.is_empty());
+        assert_eq!(cone.rays.len(), 2);
+        assert!(cone.rays.contains(&v(&[1, 1])));
+        assert!(cone.rays.contains(&v(&[-1, 1])));
+    }
+
+    #[test]
+    fn conversion_equality() {
+        // x = y in the half space z >= 0
+        let cone = conversion(3, &[v(&[1, -1, 0])], &[v(&[0, 0, 1])]).unwrap();
+        assert_eq!(cone.lines.len(), 1);
+        assert_eq!(cone.rays, vec![v(&[0, 0, 1])]);
+    }
+}
@@ -0,0 +1,4 @@
+pub mod constraint;
+pub mod conversion;
+pub mod polyhedron;
+pub mod rational;
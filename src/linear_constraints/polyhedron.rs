@@ -0,0 +1,378 @@
+use super::{
+    constraint::{Constraint, ConstraintKind, Generator, GeneratorKind, dot},
+    conversion::conversion,
+    rational::Rational,
+};
+
+// convex polyhedron over dim variables in double description: both the constraints and the
+// generators are kept minimal, an empty polyhedron has no generators
+#[derive(Clone, Debug)]
+pub struct Polyhedron {
+    dim: usize,
+    constraints: Vec<Constraint>,
+    generators: Vec<Generator>,
+}
+
+fn unit(dim: usize, i: usize) -> Vec<Rational> {
+    let mut v = vec![Rational::ZERO; dim + 1];
+    v[i] = Rational::ONE;
+    v
+}
+
+impl PartialEq for Polyhedron {
+    fn eq(&self, other: &Self) -> bool {
+        self.dim == other.dim && self.includes(other) && other.includes(self)
+    }
+}
+
+impl Polyhedron {
+    pub fn universe(dim: usize) -> Self {
+        Polyhedron {
+            dim,
+            constraints: vec![],
+            generators: std::iter::once(Generator::point(unit(dim, 0)))
+                .chain((1..=dim).map(|i| Generator::line(unit(dim, i))))
+                .collect(),
+        }
+    }
+
+    pub fn empty(dim: usize) -> Self {
+        let mut unsatisfiable = vec![Rational::ZERO; dim + 1];
+        unsatisfiable[0] = -Rational::ONE;
+        Polyhedron {
+            dim,
+            constraints: vec![Constraint::inequality(unsatisfiable)],
+            generators: vec![],
+        }
+    }
+
+    // the constraints that make the conversion overflow are dropped, one at a time
+    pub fn from_constraints(dim: usize, constraints: Vec<Constraint>) -> Self {
+        Self::try_from_constraints(dim, constraints.clone()).unwrap_or_else(|| {
+            constraints
+                .into_iter()
+                .fold(Self::universe(dim), |poly, constraint| {
+                    if poly.is_empty() {
+                        return poly;
+                    }
+                    let constraints = poly.constraints.iter().cloned().chain([constraint]);
+                    Self::try_from_constraints(dim, constraints.collect()).unwrap_or(poly)
+                })
+        })
+    }
+
+    fn try_from_constraints(dim: usize, constraints: Vec<Constraint>) -> Option<Self> {
+        let (equalities, mut inequalities): (Vec<_>, Vec<_>) = constraints
+            .into_iter()
+            .partition(|c| c.kind == ConstraintKind::Equality);
+        // xi >= 0 keeps the homogenized cone on the side of the points
+        inequalities.push(Constraint::inequality(unit(dim, 0)));
+
+        let cone = conversion(
+            dim + 1,
+            &equalities.into_iter().map(|c| c.coeffs).collect::<Vec<_>>(),
+            &inequalities
+                .into_iter()
+                .map(|c| c.coeffs)
+                .collect::<Vec<_>>(),
+        )?;
+        let generators: Vec<_> = cone
+            .rays
+            .into_iter()
+            .map(|r| match r[0] > Rational::ZERO {
+                true => Generator::point(r),
+                false => Generator::ray(r),
+            })
+            .chain(cone.lines.into_iter().map(Generator::line))
+            .collect();
+
+        if !generators.iter().any(|g| g.kind == GeneratorKind::Point) {
+            return Some(Self::empty(dim));
+        }
+        Some(Polyhedron {
+            dim,
+            constraints: Self::constraints_of(dim, &generators)?,
+            generators,
+        })
+    }
+
+    // the universe when the constraints of the generators overflow
+    pub fn from_generators(dim: usize, generators: Vec<Generator>) -> Self {
+        if !generators.iter().any(|g| g.kind == GeneratorKind::Point) {
+            return Self::empty(dim);
+        }
+        match Self::constraints_of(dim, &generators) {
+            Some(constraints) => Self::from_constraints(dim, constraints),
+            None => Self::universe(dim),
+        }
+    }
+
+    // the constraints of a cone are the generators of its dual
+    fn constraints_of(dim: usize, generators: &[Generator]) -> Option<Vec<Constraint>> {
+        let (lines, others): (Vec<_>, Vec<_>) = generators
+            .iter()
+            .partition(|g| g.kind == GeneratorKind::Line);
+        let dual = conversion(
+            dim + 1,
+            &lines
+                .into_iter()
+                .map(|g| g.coords.clone())
+                .collect::<Vec<_>>(),
+            &others
+                .into_iter()
+                .map(|g| g.coords.clone())
+                .collect::<Vec<_>>(),
+        )?;
+        let constraints = dual
+            .lines
+            .into_iter()
+            .map(Constraint::equality)
+            .chain(dual.rays.into_iter().map(Constraint::inequality))
+            // drops the positivity constraint xi >= 0
+            .filter(|c| c.coeffs[1..].iter().any(|a| !a.is_zero()))
+            .collect();
+        Some(constraints)
+    }
+
+    pub fn constraints(&self) -> &[Constraint] {
+        &self.constraints
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.generators.is_empty()
+    }
+
+    pub fn entails(&self, constraint: &Constraint) -> bool {
+        self.generators
+            .iter()
+            .all(|g| constraint.is_satisfied_by(g))
+    }
+
+    // other is a subset of self
+    pub fn includes(&self, other: &Self) -> bool {
+        other.is_empty() || (!self.is_empty() && self.constraints.iter().all(|c| other.entails(c)))
+    }
+
+    pub fn meet(&self, other: &Self) -> Self {
+        self.add_constraints(other.constraints.clone())
+    }
+
+    pub fn add_constraints(&self, constraints: Vec<Constraint>) -> Self {
+        if self.is_empty() {
+            return self.clone();
+        }
+        Self::from_constraints(
+            self.dim,
+            self.constraints
+                .iter()
+                .cloned()
+                .chain(constraints)
+                .collect(),
+        )
+    }
+
+    // convex hull
+    pub fn join(&self, other: &Self) -> Self {
+        match (self.is_empty(), other.is_empty()) {
+            (true, _) => other.clone(),
+            (_, true) => self.clone(),
+            _ => Self::from_generators(
+                self.dim,
+                self.generators
+                    .iter()
+                    .chain(other.generators.iter())
+                    .cloned()
+                    .collect(),
+            ),
+        }
+    }
+
+    // x_k := coeffs[0] + sum coeffs[i] * x_i, x_k is forgotten when its image overflows
+    pub fn affine_image(&self, k: usize, coeffs: &[Rational]) -> Self {
+        if self.is_empty() {
+            return self.clone();
+        }
+        let generators = self
+            .generators
+            .iter()
+            .map(|g| {
+                let mut coords = g.coords.clone();
+                coords[k] = dot(coeffs, &g.coords)?;
+                Some(Generator {
+                    kind: g.kind,
+                    coords,
+                })
+            })
+            .collect::<Option<_>>();
+        match generators {
+            Some(generators) => Self::from_generators(self.dim, generators),
+            None => self.forget(k),
+        }
+    }
+
+    // existential quantification of x_k
+    pub fn forget(&self, k: usize) -> Self {
+        if self.is_empty() {
+            return self.clone();
+        }
+        let mut generators = self.generators.clone();
+        generators.push(Generator::line(unit(self.dim, k)));
+        Self::from_generators(self.dim, generators)
+    }
+
+    // lower and upper bound of x_k, None when unbounded
+    pub fn bounds(&self, k: usize) -> (Option<Rational>, Option<Rational>) {
+        let mut lower = None;
+        let mut upper = None;
+        let mut lower_unbounded = false;
+        let mut upper_unbounded = false;
+        for g in self.generators.iter() {
+            // a coordinate that overflows bounds nothing
+            let Some(x) = g.coord(k) else {
+                lower_unbounded = true;
+                upper_unbounded = true;
+                continue;
+            };
+            match g.kind {
+                GeneratorKind::Point => {
+                    lower = Some(lower.map_or(x, |l: Rational| l.min(x)));
+                    upper = Some(upper.map_or(x, |u: Rational| u.max(x)));
+                }
+                GeneratorKind::Ray => {
+                    lower_unbounded |= x < Rational::ZERO;
+                    upper_unbounded |= x > Rational::ZERO;
+                }
+                GeneratorKind::Line => {
+                    lower_unbounded |= !x.is_zero();
+                    upper_unbounded |= !x.is_zero();
+                }
+            }
+        }
+        (
+            lower.filter(|_| !lower_unbounded),
+            upper.filter(|_| !upper_unbounded),
+        )
+    }
+
+    // standard widening: the constraints of self that still hold in other, plus the constraints
+    // of other that can replace one of self since self saturates them on the same generators
+    pub fn widening(&self, other: &Self) -> Self {
+        if self.is_empty() {
+            return other.clone();
+        }
+        let saturation = |c: &Constraint| -> Vec<bool> {
+            self.generators
+                .iter()
+                .map(|g| c.is_saturated_by(g))
+                .collect()
+        };
+
+        let lhs: Vec<Constraint> = self
+            .constraints
+            .iter()
+            .flat_map(Constraint::as_inequalities)
+            .collect();
+        let lhs_saturations: Vec<_> = lhs.iter().map(saturation).collect();
+
+        let stable = lhs.iter().filter(|c| other.entails(c)).cloned();
+        let replacing = other
+            .constraints
+            .iter()
+            .flat_map(Constraint::as_inequalities)
+            .filter(|c| self.entails(c) && lhs_saturations.contains(&saturation(c)));
+
+        Self::from_constraints(self.dim, stable.chain(replacing).collect())
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use crate::linear_constraints::{
+        constraint::{Constraint, Generator},
+        rational::Rational,
+    };
+
+    use super::Polyhedron;
+
+    fn v(coords: &[i64]) -> Vec<Rational> {
+        coords.iter().map(|x| Rational::from(*x)).collect()
+    }
+
+    // 0 <= x <= 2, 0 <= y <= 2
+    fn square() -> Polyhedron {
+        Polyhedron::from_constraints(
+            2,
+            vec![
+                Constraint::inequality(v(&[0, 1, 0])),
+                Constraint::inequality(v(&[2, -1, 0])),
+                Constraint::inequality(v(&[0, 0, 1])),
+                Constraint::inequality(v(&[2, 0, -1])),
+            ],
+        )
+    }
+
+    #[test]
+    fn polyhedron_double_description() {
+        let p = square();
+        assert_eq!(p.generators.len(), 4);
+        assert_eq!(p.constraints().len(), 4);
+        assert_eq!(
+            p.bounds(1),
+            (Some(Rational::ZERO), Some(Rational::from(2_i64)))
+        );
+
+        // x + y <= 5 is redundant
+        let q = p.add_constraints(vec![Constraint::inequality(v(&[5, -1, -1]))]);
+        assert_eq!(q.constraints().len(), 4);
+        assert_eq!(p, q);
+
+        let empty = p.add_constraints(vec![Constraint::inequality(v(&[-3, 1, 0]))]);
+        assert!(empty.is_empty());
+    }
+
+    #[test]
+    fn polyhedron_join() {
+        let origin = Polyhedron::from_generators(2, vec![Generator::point(v(&[1, 0, 0]))]);
+        let point = Polyhedron::from_generators(2, vec![Generator::point(v(&[1, 2, 2]))]);
+        let segment = origin.join(&point);
+        // x = y, 0 <= x <= 2
+        assert!(segment.entails(&Constraint::equality(v(&[0, 1, -1]))));
+        assert!(square().includes(&segment));
+        assert!(!segment.includes(&square()));
+        assert_eq!(
+            segment.bounds(2),
+            (Some(Rational::ZERO), Some(Rational::from(2_i64)))
+        );
+    }
+
+    #[test]
+    fn polyhedron_affine_image() {
+        // y := x + y on the square gives 0 <= x <= 2, x <= y <= x + 2
+        let p = square().affine_image(2, &v(&[0, 1, 1]));
+        assert_eq!(
+            p.bounds(2),
+            (Some(Rational::ZERO), Some(Rational::from(4_i64)))
+        );
+        assert!(p.entails(&Constraint::inequality(v(&[0, -1, 1]))));
+
+        let p = p.forget(1);
+        assert_eq!(p.bounds(1), (None, None));
+        assert_eq!(
+            p.bounds(2),
+            (Some(Rational::ZERO), Some(Rational::from(4_i64)))
+        );
+    }
+
+    #[test]
+    fn polyhedron_widening() {
+        // from { x = 0, y = 0 } to { 0 <= x <= 1, y = x } the widening keeps x = y and x >= 0
+        let lhs = Polyhedron::from_generators(2, vec![Generator::point(v(&[1, 0, 0]))]);
+        let rhs = lhs.join(&Polyhedron::from_generators(
+            2,
+            vec![Generator::point(v(&[1, 1, 1]))],
+        ));
+        let widened = lhs.widening(&rhs);
+        assert!(widened.entails(&Constraint::equality(v(&[0, 1, -1]))));
+        assert_eq!(widened.bounds(1), (Some(Rational::ZERO), None));
+    }
+}
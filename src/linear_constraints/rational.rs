@@ -0,0 +1,217 @@
+use core::fmt;
+use std::{cmp::Ordering, ops::Neg};
+
+pub fn gcd(a: i128, b: i128) -> i128 {
+    let (mut a, mut b) = (a.abs(), b.abs());
+    while b != 0 {
+        (a, b) = (b, a % b);
+    }
+    a
+}
+
+// exact fraction num / den, always normalized with den > 0 and gcd(num, den) = 1
+#[derive(Clone, Copy, Debug, PartialEq, Eq, Hash)]
+pub struct Rational {
+    num: i128,
+    den: i128,
+}
+
+impl Rational {
+    pub const ZERO: Rational = Rational { num: 0, den: 1 };
+    pub const ONE: Rational = Rational { num: 1, den: 1 };
+
+    pub fn new(num: i128, den: i128) -> Self {
+        assert!(den != 0, "Rational with zero denominator");
+        let g = gcd(num, den);
+        let sign = den.signum();
+        Rational {
+            num: sign * num / g,
+            den: sign * den / g,
+        }
+    }
+
+    pub fn num(&self) -> i128 {
+        self.num
+    }
+
+    pub fn den(&self) -> i128 {
+        self.den
+    }
+
+    pub fn is_zero(&self) -> bool {
+        self.num == 0
+    }
+
+    pub fn signum(&self) -> i128 {
+        self.num.signum()
+    }
+
+    pub fn abs(&self) -> Self {
+        Rational {
+            num: self.num.abs(),
+            den: self.den,
+        }
+    }
+
+    pub fn floor(&self) -> i128 {
+        self.num.div_euclid(self.den)
+    }
+
+    pub fn ceil(&self) -> i128 {
+        -(-self.num).div_euclid(self.den)
+    }
+
+    // None when a result does not fit, i128::MIN is never a numerator so that negating is safe
+    fn checked_new(num: Option<i128>, den: Option<i128>) -> Option<Self> {
+        match (num, den) {
+            (Some(num), Some(den)) if num != i128::MIN && den != i128::MIN => {
+                Some(Rational::new(num, den))
+            }
+            _ => None,
+        }
+    }
+
+    pub fn checked_add(self, rhs: Self) -> Option<Self> {
+        let g = gcd(self.den, rhs.den);
+        let lhs_num = self.num.checked_mul(rhs.den / g)?;
+        let rhs_num = rhs.num.checked_mul(self.den / g)?;
+        Self::checked_new(
+            lhs_num.checked_add(rhs_num),
+            (self.den / g).checked_mul(rhs.den),
+        )
+    }
+
+    pub fn checked_mul(self, rhs: Self) -> Option<Self> {
+        // cross simplification keeps the intermediate products small
+        let g1 = gcd(self.num, rhs.den).max(1);
+        let g2 = gcd(rhs.num, self.den).max(1);
+        Self::checked_new(
+            (self.num / g1).checked_mul(rhs.num / g2),
+            (self.den / g2).checked_mul(rhs.den / g1),
+        )
+    }
+
+    pub fn checked_div(self, rhs: Self) -> Option<Self> {
+        assert!(!rhs.is_zero(), "Rational division by zero");
+        self.checked_mul(Rational::new(rhs.den, rhs.num))
+    }
+}
+
+impl From<i64> for Rational {
+    fn from(value: i64) -> Self {
+        Rational {
+            num: value as i128,
+            den: 1,
+        }
+    }
+}
+
+impl From<i128> for Rational {
+    fn from(value: i128) -> Self {
+        Rational { num: value, den: 1 }
+    }
+}
+
+impl Neg for Rational {
+    type Output = Self;
+    fn neg(self) -> Self::Output {
+        Rational {
+            num: -self.num,
+            den: self.den,
+        }
+    }
+}
+
+impl PartialOrd for Rational {
+    fn partial_cmp(&self, other: &Self) -> Option<Ordering> {
+        Some(self.cmp(other))
+    }
+}
+
+impl Ord for Rational {
+    // compares the continued fractions, no product is computed so nothing can overflow
+    fn cmp(&self, other: &Self) -> Ordering {
+        let (mut lhs, mut rhs) = ((self.num, self.den), (other.num, other.den));
+        let mut flipped = false;
+        loop {
+            let order = lhs.0.div_euclid(lhs.1).cmp(&rhs.0.div_euclid(rhs.1));
+            let (lhs_rem, rhs_rem) = (lhs.0.rem_euclid(lhs.1), rhs.0.rem_euclid(rhs.1));
+            let order = match (order, lhs_rem, rhs_rem) {
+                (Ordering::Equal, 0, 0) => Ordering::Equal,
+                (Ordering::Equal, 0, _) => Ordering::Less,
+                (Ordering::Equal, _, 0) => Ordering::Greater,
+                (Ordering::Equal, _, _) => {
+                    // the smaller fractional part has the larger inverse
+                    (lhs, rhs) = ((lhs.1, lhs_rem), (rhs.1, rhs_rem));
+                    flipped = !flipped;
+                    continue;
+                }
+                (order, _, _) => order,
+            };
+            return match flipped {
+                true => order.reverse(),
+                false => order,
+            };
+        }
+    }
+}
+
+impl fmt::Display for Rational {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        match self.den {
+            1 => write!(f, "{}", self.num),
+            _ => write!(f, "{}/{}", self.num, self.den),
+        }
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use super::Rational;
+
+    #[test]
+    fn rational_normal_form() {
+        assert_eq!(Rational::new(2, 4), Rational::new(1, 2));
+        assert_eq!(Rational::new(3, -6), Rational::new(-1, 2));
+        assert_eq!(Rational::new(0, -5), Rational::ZERO);
+    }
+
+    #[test]
+    fn rational_arithmetic() {
+        let half = Rational::new(1, 2);
+        let third = Rational::new(1, 3);
+        assert_eq!(half.checked_add(third), Some(Rational::new(5, 6)));
+        assert_eq!(half.checked_add(-third), Some(Rational::new(1, 6)));
+        assert_eq!(half.checked_mul(third), Some(Rational::new(1, 6)));
+        assert_eq!(half.checked_div(third), Some(Rational::new(3, 2)));
+        assert!(third < half);
+        assert!(-half < third);
+    }
+
+    #[test]
+    fn rational_overflow() {
+        let max = Rational::from(i128::MAX);
+        assert_eq!(max.checked_add(Rational::ONE), None);
+        assert_eq!(max.checked_mul(Rational::from(2_i64)), None);
+        assert_eq!((-max).checked_add(-Rational::ONE), None);
+        assert_eq!(
+            max.checked_mul(Rational::new(1, 2)),
+            Some(Rational::new(i128::MAX, 2))
+        );
+
+        // the comparison never overflows
+        let almost = Rational::new(i128::MAX - 1, i128::MAX);
+        assert!(almost < Rational::ONE);
+        assert!(Rational::new(i128::MAX - 2, i128::MAX - 1) < almost);
+        assert!(-max < -almost);
+    }
+
+    #[test]
+    fn rational_rounding() {
+        assert_eq!(Rational::new(7, 2).floor(), 3);
+        assert_eq!(Rational::new(7, 2).ceil(), 4);
+        assert_eq!(Rational::new(-7, 2).floor(), -4);
+        assert_eq!(Rational::new(-7, 2).ceil(), -3);
+        assert_eq!(Rational::from(5_i64).ceil(), 5);
+    }
+}
@@ -2,42 +2,292 @@ use std::{
     env,
     fs::{self},
     path::Path,
+    process,
 };
 
-use abstract_domains::interval::Interval;
-use grammar::StatementParser;
-use interpreter::Interpreter;
+use abstract_domains::{
+    congruence::Congruence,
+    constant::Constant,
+    interval::{Bounds, Interval},
+    known_bits::KnownBits,
+    product::{IntervalCongruence, Product},
+    sign::Sign,
+};
+use abstract_states::{
+    abstract_state::AbstractState,
+    octagon::Octagon,
+    partitioned::Partitioned,
+    polyhedra::Polyhedra,
+    powerset::{MergeOldest, Powerset},
+    zone::Zone,
+};
+use interpreter::{Annotations, IntegerType, Interpreter, Machine, OverflowBehavior, Partitioning};
 use lalrpop_util::lalrpop_mod;
-use utils::{decorate_code_with_analysis, extract_vars_init};
+use parser::ast::{Position, Statement};
+use report::Configuration;
+use state::State;
+use utils::{decorate_code_with_analysis, extract_partitioning};
 
 mod abstract_domains;
+mod abstract_states;
+mod diagnostics;
 mod interpreter;
+mod linear_constraints;
 mod parser;
 mod propagation_algo;
+mod report;
 mod state;
 mod utils;
 
-lalrpop_mod!(grammar, "/parser/grammar.rs");
+// the parser takes the recovered errors as a Vec
+lalrpop_mod!(
+    #[allow(clippy::ptr_arg)]
+    grammar,
+    "/parser/grammar.rs"
+);
 
 fn main() {
     let args: Vec<String> = env::args().collect();
 
     let file = args[1].as_str();
+    let (domain, options) = match args.get(2) {
+        Some(domain) if !domain.starts_with("--") => (domain.as_str(), &args[3..]),
+        _ => ("interval", &args[2..]),
+    };
 
     let source_code = std::fs::read_to_string(file).unwrap();
-    let lexer = parser::lexer::Lexer::new(&source_code);
-    let program = StatementParser::new().parse(&source_code, lexer).unwrap();
+    let program = match diagnostics::parse(&source_code) {
+        Ok(program) => program,
+        Err(errors) => {
+            for error in &errors {
+                eprintln!("{}\n", error.render(file, &source_code));
+            }
+            eprintln!("error: aborting due to {} previous error(s)", errors.len());
+            process::exit(1);
+        }
+    };
 
     println!("Program: {:#?}", &program);
 
-    let given_vars = extract_vars_init(&source_code);
-    let mut interpreter = Interpreter::<Interval>::build(&program, given_vars);
+    let mut partitioning = extract_partitioning(&source_code);
+    let (mut annotate, mut json, mut sarif) = (false, false, false);
+    let (mut integers, mut overflow) = (None, OverflowBehavior::Wrap);
+    let mut options = options.iter();
+    while let Some(option) = options.next() {
+        match option.as_str() {
+            "--partition" => partitioning.all_conditionals = true,
+            "--merge-after-loops" => partitioning.merge_after_loops = true,
+            "--annotate" => annotate = true,
+            "--json" => json = true,
+            "--sarif" => sarif = true,
+            "--unroll" => match options.next().and_then(|n| n.parse().ok()) {
+                Some(n) => partitioning.unroll = n,
+                None => {
+                    eprintln!("--unroll expects the number of iterations to unroll");
+                    process::exit(1);
+                }
+            },
+            "--integers" => match options.next().map(String::as_str) {
+                Some("i64") => integers = Some(IntegerType::I64),
+                Some("i32") => integers = Some(IntegerType::I32),
+                Some("u32") => integers = Some(IntegerType::U32),
+                _ => {
+                    eprintln!("--integers expects one of: i64, i32, u32");
+                    process::exit(1);
+                }
+            },
+            "--overflow" => match options.next().map(String::as_str) {
+                Some("wrap") => overflow = OverflowBehavior::Wrap,
+                Some("trap") => overflow = OverflowBehavior::Trap,
+                _ => {
+                    eprintln!("--overflow expects one of: wrap, trap");
+                    process::exit(1);
+                }
+            },
+            _ => {
+                eprintln!(
+                    "Unknown option {option}, expected one of: --partition, --unroll <n>, --merge-after-loops, --annotate, --json, --sarif, --integers <i64|i32|u32>, --overflow <wrap|trap>"
+                );
+                process::exit(1);
+            }
+        }
+    }
+
+    let bounds = matches!(
+        domain,
+        "interval" | "interval-congruence" | "powerset" | "powerset-oldest"
+    )
+    .then(Bounds::from_env)
+    .transpose()
+    .unwrap_or_else(|error| {
+        eprintln!("{error}");
+        process::exit(1);
+    });
+    let narrowing_steps = env::var("NARROWING_STEPS")
+        .unwrap_or("0".to_string())
+        .parse()
+        .unwrap_or(0_usize);
+    println!("Narrowing steps: {narrowing_steps}");
+    let options = Options {
+        partitioning,
+        narrowing_steps,
+        annotate,
+        json,
+        sarif,
+        config: Configuration {
+            domain,
+            bounds,
+            machine: integers.map(|integers| Machine { integers, overflow }),
+        },
+    };
+    let bounds = bounds.unwrap_or_default();
+
+    match domain {
+        "interval" => analyze::<State<Interval>>(file, &source_code, &program, options, bounds),
+        "sign" => analyze::<State<Sign>>(file, &source_code, &program, options, ()),
+        "constant" => analyze::<State<Constant>>(file, &source_code, &program, options, ()),
+        "congruence" => analyze::<State<Congruence>>(file, &source_code, &program, options, ()),
+        "known-bits" => analyze::<State<KnownBits>>(file, &source_code, &program, options, ()),
+        "interval-congruence" => {
+            analyze::<State<Product<Interval, Congruence, IntervalCongruence>>>(
+                file,
+                &source_code,
+                &program,
+                options,
+                (bounds, ()),
+            )
+        }
+        "powerset" => {
+            analyze::<Powerset<State<Interval>, 4>>(file, &source_code, &program, options, bounds)
+        }
+        "powerset-oldest" => analyze::<Powerset<State<Interval>, 4, MergeOldest>>(
+            file,
+            &source_code,
+            &program,
+            options,
+            bounds,
+        ),
+        "zone" => analyze::<Zone>(file, &source_code, &program, options, ()),
+        "octagon" => analyze::<Octagon>(file, &source_code, &program, options, ()),
+        "polyhedra" => analyze::<Polyhedra>(file, &source_code, &program, options, ()),
+        _ => {
+            eprintln!(
+                "Unknown abstract domain {domain}, expected one of: interval, sign, constant, congruence, known-bits, interval-congruence, powerset, powerset-oldest, zone, octagon, polyhedra"
+            );
+            process::exit(1);
+        }
+    }
+}
+
+// how the analysis runs and what it reports, besides the abstract state
+struct Options<'a> {
+    partitioning: Partitioning,
+    // the decreasing iterations that refine each loop invariant, read from NARROWING_STEPS
+    narrowing_steps: usize,
+    // every program point is annotated in the .analysis file, not only the loop heads
+    annotate: bool,
+    // the results are also written to a .json file
+    json: bool,
+    // the alarms are also written to a .sarif file
+    sarif: bool,
+    config: Configuration<'a>,
+}
+
+// traces are kept apart only when some partitioning is requested
+fn analyze<'a, S: AbstractState<'a>>(
+    file: &str,
+    source_code: &'a str,
+    program: &'a Statement<'a>,
+    options: Options,
+    ctx: S::Context,
+) {
+    match options.partitioning.is_enabled() {
+        true => run::<Partitioned<S>>(file, source_code, program, options, ctx),
+        false => run::<S>(file, source_code, program, options, ctx),
+    }
+}
+
+fn run<'a, S: AbstractState<'a>>(
+    file: &str,
+    source_code: &'a str,
+    program: &'a Statement<'a>,
+    options: Options,
+    ctx: S::Context,
+) {
+    // the json output has every program point
+    let mut interpreter = Interpreter::<S>::build(program, &ctx)
+        .with_partitioning(options.partitioning)
+        .with_narrowing_steps(options.narrowing_steps)
+        .with_machine(options.config.machine)
+        .with_annotations(options.annotate || options.json);
     let invariants = interpreter.interpret();
+    for (pos, assertion) in interpreter.assertions() {
+        println!("Assertion at line {}: {}", pos.line + 1, assertion.verdict);
+    }
+    for division in interpreter.divisions().values() {
+        let Position { line, clm } = &division.span.start;
+        let verdict = report::division_verdict(division.verdict);
+        println!(
+            "Division at line {}, column {}: {verdict}",
+            line + 1,
+            clm + 1
+        );
+    }
+    for overflow in interpreter.overflows().values() {
+        let Position { line, clm } = &overflow.span.start;
+        let verdict = report::overflow_verdict(overflow.verdict);
+        println!(
+            "Overflow at line {}, column {}: {verdict}",
+            line + 1,
+            clm + 1
+        );
+    }
+    for alarm in report::dead_code(&interpreter) {
+        let Position { line, clm } = &alarm.span.start;
+        println!(
+            "{} at line {}, column {}: {}",
+            alarm.rule.label(),
+            line + 1,
+            clm + 1,
+            alarm.message
+        );
+    }
+
+    if options.json {
+        let output_file = Path::new(file).with_extension("json");
+        fs::write(
+            output_file,
+            report::to_json(&options.config, program, &interpreter, &invariants),
+        )
+        .expect("Unable to write file");
+    }
+
+    if options.sarif {
+        let output_file = Path::new(file).with_extension("sarif");
+        fs::write(
+            output_file,
+            report::to_sarif(
+                file,
+                &env::current_dir().expect("Unable to read the current directory"),
+                &report::alarms(&interpreter),
+            ),
+        )
+        .expect("Unable to write file");
+    }
 
     let output_file = Path::new(file).with_extension("analysis");
+    let no_annotations = Annotations::new();
     fs::write(
         output_file,
-        decorate_code_with_analysis(source_code.clone(), invariants),
+        decorate_code_with_analysis(
+            source_code.to_string(),
+            invariants,
+            interpreter.assertions(),
+            match options.annotate {
+                true => interpreter.annotations(),
+                false => &no_annotations,
+            },
+        ),
     )
     .expect("Unable to write file");
 }
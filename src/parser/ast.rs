@@ -4,44 +4,133 @@ use std::{
     ops::{Neg, Not},
 };
 
-#[derive(Hash, PartialOrd, Ord, Eq, Debug, Clone, PartialEq)]
+// 0-based line and byte column in the line
+#[derive(Hash, PartialOrd, Ord, Eq, Debug, Clone, PartialEq, Default)]
 pub struct Position {
     pub line: usize,
     pub clm: usize,
 }
 
+impl Position {
+    // position of a byte offset of the source
+    pub fn at(source: &str, offset: usize) -> Self {
+        let before = &source[..offset];
+        let line_start = before.rfind('\n').map_or(0, |i| i + 1);
+        Position {
+            line: before.matches('\n').count(),
+            clm: offset - line_start,
+        }
+    }
+}
+
+// range of the source of a node, end excluded
+#[derive(Clone, Debug, Default, PartialEq)]
+pub struct Span {
+    pub start: Position,
+    pub end: Position,
+    // the node is added by a normal form and is not written in the source
+    pub desugared: bool,
+}
+
+impl Span {
+    pub fn new(source: &str, start: usize, end: usize) -> Self {
+        Span {
+            start: Position::at(source, start),
+            end: Position::at(source, end),
+            desugared: false,
+        }
+    }
+
+    // smallest span covering both
+    pub fn to(&self, other: &Span) -> Span {
+        Span {
+            start: self.start.clone().min(other.start.clone()),
+            end: self.end.clone().max(other.end.clone()),
+            desugared: false,
+        }
+    }
+
+    // the same range, for a node added by a normal form
+    pub fn desugared(&self) -> Span {
+        Span {
+            desugared: true,
+            ..self.clone()
+        }
+    }
+}
+
 #[derive(Clone, Debug, PartialEq)]
 pub enum Statement<'a> {
     Assignment(Assignment<'a>),
-    Skip,
+    Skip(Span),
     Composition {
         lhs: Box<Statement<'a>>,
         rhs: Box<Statement<'a>>,
+        span: Span,
     },
     Conditional {
         guard: Box<BooleanExp<'a>>,
         true_branch: Box<Statement<'a>>,
         false_branch: Box<Statement<'a>>,
+        span: Span,
     },
     While {
-        pos: Position,
         guard: Box<BooleanExp<'a>>,
         body: Box<Statement<'a>>,
+        span: Span,
+    },
+    // restricts the analysis to the traces satisfying the guard
+    Assume {
+        guard: Box<BooleanExp<'a>>,
+        span: Span,
+    },
+    // checked against the states reaching it, the traces violating it are then discarded
+    Assert {
+        guard: Box<BooleanExp<'a>>,
+        span: Span,
+    },
+    // non-deterministic choice between two statements
+    Either {
+        lhs: Box<Statement<'a>>,
+        rhs: Box<Statement<'a>>,
+        span: Span,
     },
 }
 
 impl<'a> Statement<'a> {
+    pub fn span(&self) -> &Span {
+        match self {
+            Statement::Assignment(Assignment { span, .. })
+            | Statement::Skip(span)
+            | Statement::Composition { span, .. }
+            | Statement::Conditional { span, .. }
+            | Statement::While { span, .. }
+            | Statement::Assume { span, .. }
+            | Statement::Assert { span, .. }
+            | Statement::Either { span, .. } => span,
+        }
+    }
+
     pub fn extract_vars(&self, vars: &mut HashSet<&'a str>) {
         match self {
-            Statement::Skip => (),
-            Statement::Assignment(Assignment { var, value: _ }) => {
+            Statement::Skip(_) => (),
+            Statement::Assignment(Assignment {
+                var,
+                value: _,
+                span: _,
+            }) => {
                 vars.insert(var);
             }
-            Statement::Composition { lhs, rhs } => {
+            Statement::Assume { guard, span: _ } | Statement::Assert { guard, span: _ } => {
+                guard.extract_vars(vars)
+            }
+            Statement::Composition { lhs, rhs, span: _ }
+            | Statement::Either { lhs, rhs, span: _ } => {
                 lhs.extract_vars(vars);
                 rhs.extract_vars(vars);
             }
             Statement::Conditional {
+                span: _,
                 guard,
                 true_branch: lhs,
                 false_branch: rhs,
@@ -51,7 +140,7 @@ impl<'a> Statement<'a> {
                 rhs.extract_vars(vars);
             }
             Statement::While {
-                pos: _,
+                span: _,
                 guard,
                 body,
             } => {
@@ -63,11 +152,19 @@ impl<'a> Statement<'a> {
 
     pub fn extract_constant(&self, consts: &mut HashSet<i64>) {
         match self {
-            Statement::Skip => (),
-            Statement::Assignment(Assignment { var: _, value }) => {
+            Statement::Skip(_) => (),
+            Statement::Assignment(Assignment {
+                var: _,
+                value,
+                span: _,
+            }) => {
                 value.extract_constants(consts);
             }
+            Statement::Assume { guard, span: _ } | Statement::Assert { guard, span: _ } => {
+                guard.extract_constant(consts)
+            }
             Statement::Conditional {
+                span: _,
                 guard,
                 true_branch,
                 false_branch,
@@ -76,12 +173,13 @@ impl<'a> Statement<'a> {
                 true_branch.extract_constant(consts);
                 false_branch.extract_constant(consts);
             }
-            Statement::Composition { lhs, rhs } => {
+            Statement::Composition { lhs, rhs, span: _ }
+            | Statement::Either { lhs, rhs, span: _ } => {
                 lhs.extract_constant(consts);
                 rhs.extract_constant(consts);
             }
             Statement::While {
-                pos: _,
+                span: _,
                 guard,
                 body,
             } => {
@@ -96,47 +194,82 @@ impl<'a> Statement<'a> {
 pub struct Assignment<'a> {
     pub var: &'a str,
     pub value: Box<ArithmeticExp<'a>>,
+    pub span: Span,
 }
 
 #[derive(Clone, Debug, PartialEq)]
 pub enum ArithmeticExp<'a> {
-    Integer(i64),
-    Variable(&'a str),
+    Integer(i64, Span),
+    Variable(&'a str, Span),
+    Negation(Box<ArithmeticExp<'a>>, Span),
+    // an input of the environment: rand(low, upper) is any integer of [low, upper], ? any integer
+    Random {
+        low: i64,
+        upper: i64,
+        span: Span,
+    },
+    Unknown(Span),
     BinaryOperation {
         lhs: Box<ArithmeticExp<'a>>,
         operator: Operator,
         rhs: Box<ArithmeticExp<'a>>,
+        span: Span,
     },
 }
 
 impl<'a> ArithmeticExp<'a> {
+    pub fn span(&self) -> &Span {
+        match self {
+            ArithmeticExp::Integer(_, span)
+            | ArithmeticExp::Variable(_, span)
+            | ArithmeticExp::Negation(_, span)
+            | ArithmeticExp::Random { span, .. }
+            | ArithmeticExp::Unknown(span)
+            | ArithmeticExp::BinaryOperation { span, .. } => span,
+        }
+    }
+
     pub fn extract_constants(&self, consts: &mut HashSet<i64>) {
         match self {
-            ArithmeticExp::Integer(x) => {
+            ArithmeticExp::Integer(x, _) => {
                 consts.insert(*x);
             }
             ArithmeticExp::BinaryOperation {
                 lhs,
                 operator: _,
                 rhs,
+                span: _,
             } => {
                 lhs.extract_constants(consts);
                 rhs.extract_constants(consts);
             }
+            ArithmeticExp::Negation(exp, _) => exp.extract_constants(consts),
+            ArithmeticExp::Random {
+                low,
+                upper,
+                span: _,
+            } => {
+                consts.insert(*low);
+                consts.insert(*upper);
+            }
             _ => (),
         }
     }
 
     pub fn extract_vars(&self, vars: &mut HashSet<&'a str>) {
         match self {
-            ArithmeticExp::Variable(x) => {
+            ArithmeticExp::Variable(x, _) => {
                 vars.insert(*x);
             }
-            ArithmeticExp::Integer(_) => (),
+            ArithmeticExp::Integer(..)
+            | ArithmeticExp::Random { .. }
+            | ArithmeticExp::Unknown(_) => (),
+            ArithmeticExp::Negation(exp, _) => exp.extract_vars(vars),
             ArithmeticExp::BinaryOperation {
                 lhs,
                 operator: _,
                 rhs,
+                span: _,
             } => {
                 lhs.extract_vars(vars);
                 rhs.extract_vars(vars);
@@ -151,29 +284,68 @@ pub enum Operator {
     Sub,
     Mul,
     Div,
+    // remainder of the truncated division, its sign is the one of the dividend
+    Mod,
 }
 
 #[derive(Clone, Debug, PartialEq)]
 pub struct ArithmeticCondition<'a> {
     pub lhs: Box<ArithmeticExp<'a>>,
     pub operator: ConditionOperator,
+    // the span of the comparison in the source, lhs may be built from both of its sides
+    pub span: Span,
 }
 
 impl<'a> ArithmeticCondition<'a> {
+    // lhs comparison rhs becomes lhs - rhs operator 0, over the integers a <= b is a - b - 1 < 0
+    // and a > b is a - b - 1 >= 0
     pub fn normal_form(
         lhs: Box<ArithmeticExp<'a>>,
-        operator: ConditionOperator,
+        comparison: Comparison,
         rhs: Box<ArithmeticExp<'a>>,
     ) -> Self {
-        if *rhs.as_ref() == ArithmeticExp::Integer(0) {
-            return ArithmeticCondition { lhs, operator };
+        let (operator, offset) = match comparison {
+            Comparison::Equal => (ConditionOperator::Equal, 0),
+            Comparison::NotEqual => (ConditionOperator::NotEqual, 0),
+            Comparison::Less => (ConditionOperator::StrictlyLess, 0),
+            Comparison::LessOrEqual => (ConditionOperator::StrictlyLess, 1),
+            Comparison::Greater => (ConditionOperator::GreaterOrEqual, 1),
+            Comparison::GreaterOrEqual => (ConditionOperator::GreaterOrEqual, 0),
+        };
+        let span = lhs.span().to(rhs.span());
+        let rhs_span = rhs.span().clone();
+        let offset_span = rhs_span.desugared();
+        let rhs = match (rhs.as_ref(), offset) {
+            (ArithmeticExp::Integer(c, _), _) if c.checked_add(offset).is_some() => {
+                Box::new(ArithmeticExp::Integer(c + offset, rhs_span))
+            }
+            (_, 0) => rhs,
+            _ => Box::new(ArithmeticExp::BinaryOperation {
+                lhs: rhs,
+                operator: Operator::Add,
+                rhs: Box::new(ArithmeticExp::Integer(offset, offset_span.clone())),
+                span: offset_span,
+            }),
+        };
+
+        if let ArithmeticExp::Integer(0, _) = rhs.as_ref() {
+            return ArithmeticCondition {
+                lhs,
+                operator,
+                span,
+            };
         }
         let lhs = Box::new(ArithmeticExp::BinaryOperation {
             lhs,
             operator: Operator::Sub,
             rhs,
+            span: span.desugared(),
         });
-        ArithmeticCondition { lhs, operator }
+        ArithmeticCondition {
+            lhs,
+            operator,
+            span,
+        }
     }
 }
 
@@ -189,15 +361,17 @@ impl<'a> Not for ArithmeticCondition<'a> {
 
 #[derive(Clone, Debug, PartialEq)]
 pub enum BooleanExp<'a> {
-    Boolean(bool),
+    Boolean(bool, Span),
     ArithmeticCondition(ArithmeticCondition<'a>),
     And {
         lhs: Box<BooleanExp<'a>>,
         rhs: Box<BooleanExp<'a>>,
+        span: Span,
     },
     Or {
         lhs: Box<BooleanExp<'a>>,
         rhs: Box<BooleanExp<'a>>,
+        span: Span,
     },
 }
 
@@ -205,29 +379,44 @@ impl<'a> Not for BooleanExp<'a> {
     type Output = BooleanExp<'a>;
     fn not(self) -> Self::Output {
         match self {
-            BooleanExp::Boolean(x) => BooleanExp::Boolean(!x),
+            BooleanExp::Boolean(x, span) => BooleanExp::Boolean(!x, span),
             BooleanExp::ArithmeticCondition(x) => BooleanExp::ArithmeticCondition(!x),
-            BooleanExp::And { lhs, rhs } => BooleanExp::Or {
+            BooleanExp::And { lhs, rhs, span } => BooleanExp::Or {
                 lhs: Box::new(!*lhs),
                 rhs: Box::new(!*rhs),
+                span,
             },
 
-            BooleanExp::Or { lhs, rhs } => BooleanExp::And {
+            BooleanExp::Or { lhs, rhs, span } => BooleanExp::And {
                 lhs: Box::new(!*lhs),
                 rhs: Box::new(!*rhs),
+                span,
             },
         }
     }
 }
 
 impl<'a> BooleanExp<'a> {
+    pub fn span(&self) -> &Span {
+        match self {
+            BooleanExp::Boolean(_, span)
+            | BooleanExp::ArithmeticCondition(ArithmeticCondition { span, .. })
+            | BooleanExp::And { span, .. }
+            | BooleanExp::Or { span, .. } => span,
+        }
+    }
+
     pub fn extract_constant(&self, consts: &mut HashSet<i64>) {
         match self {
-            BooleanExp::ArithmeticCondition(ArithmeticCondition { lhs, operator: _ }) => {
+            BooleanExp::ArithmeticCondition(ArithmeticCondition {
+                lhs,
+                operator: _,
+                span: _,
+            }) => {
                 lhs.extract_constants(consts);
                 consts.insert(0);
             }
-            BooleanExp::And { lhs, rhs } | BooleanExp::Or { lhs, rhs } => {
+            BooleanExp::And { lhs, rhs, span: _ } | BooleanExp::Or { lhs, rhs, span: _ } => {
                 lhs.extract_constant(consts);
                 rhs.extract_constant(consts);
             }
@@ -237,11 +426,15 @@ impl<'a> BooleanExp<'a> {
 
     pub fn extract_vars(&self, vars: &mut HashSet<&'a str>) {
         match self {
-            BooleanExp::Boolean(_) => (),
-            BooleanExp::ArithmeticCondition(ArithmeticCondition { lhs, operator: _ }) => {
+            BooleanExp::Boolean(..) => (),
+            BooleanExp::ArithmeticCondition(ArithmeticCondition {
+                lhs,
+                operator: _,
+                span: _,
+            }) => {
                 lhs.extract_vars(vars);
             }
-            BooleanExp::And { lhs, rhs } | BooleanExp::Or { lhs, rhs } => {
+            BooleanExp::And { lhs, rhs, span: _ } | BooleanExp::Or { lhs, rhs, span: _ } => {
                 lhs.extract_vars(vars);
                 rhs.extract_vars(vars)
             }
@@ -249,6 +442,17 @@ impl<'a> BooleanExp<'a> {
     }
 }
 
+// comparisons of the surface syntax, normalized to a ConditionOperator against 0
+#[derive(Clone, Copy, Debug, PartialEq)]
+pub enum Comparison {
+    Equal,
+    NotEqual,
+    Less,
+    LessOrEqual,
+    Greater,
+    GreaterOrEqual,
+}
+
 #[derive(Clone, Copy, Debug, PartialEq)]
 pub enum ConditionOperator {
     Equal,
@@ -268,3 +472,310 @@ impl Neg for ConditionOperator {
         }
     }
 }
+
+#[cfg(test)]
+mod test {
+    use std::fmt::Debug;
+
+    use crate::{
+        grammar::{ArithmeticExpParser, BooleanExpParser, StatementParser},
+        parser::lexer::Lexer,
+    };
+
+    use super::{
+        ArithmeticCondition, ArithmeticExp, Assignment, BooleanExp, ConditionOperator, Operator,
+        Position, Span, Statement,
+    };
+
+    // the Debug output of a tree without its spans, the trees are compared by structure
+    fn erased(tree: &impl Debug) -> String {
+        let mut debug = format!("{tree:?}");
+        while let Some(start) = debug.find("Span {") {
+            let mut depth = 0;
+            let len = debug[start..]
+                .find(|c| {
+                    match c {
+                        '{' => depth += 1,
+                        '}' => depth -= 1,
+                        _ => return false,
+                    }
+                    depth == 0
+                })
+                .unwrap();
+            debug.replace_range(start..=start + len, "Span");
+        }
+        debug
+    }
+
+    #[track_caller]
+    fn assert_same<T: Debug>(lhs: T, rhs: T) {
+        assert_eq!(erased(&lhs), erased(&rhs));
+    }
+
+    fn bexp(src: &'static str) -> BooleanExp<'static> {
+        *BooleanExpParser::new()
+            .parse(src, &mut vec![], Lexer::new(src))
+            .unwrap()
+    }
+
+    fn cond(src: &'static str) -> ArithmeticCondition<'static> {
+        match bexp(src) {
+            BooleanExp::ArithmeticCondition(cond) => cond,
+            _ => panic!("{src} is not an arithmetic condition"),
+        }
+    }
+
+    fn var(name: &'static str) -> Box<ArithmeticExp<'static>> {
+        Box::new(ArithmeticExp::Variable(name, Span::default()))
+    }
+
+    fn op(
+        lhs: Box<ArithmeticExp<'static>>,
+        operator: Operator,
+        rhs: Box<ArithmeticExp<'static>>,
+    ) -> Box<ArithmeticExp<'static>> {
+        Box::new(ArithmeticExp::BinaryOperation {
+            lhs,
+            operator,
+            rhs,
+            span: Span::default(),
+        })
+    }
+
+    fn int(c: i64) -> Box<ArithmeticExp<'static>> {
+        Box::new(ArithmeticExp::Integer(c, Span::default()))
+    }
+
+    #[test]
+    fn comparisons_normal_form() {
+        let x_minus = |c| op(var("x"), Operator::Sub, int(c));
+        assert_eq!(cond("x < 0").operator, ConditionOperator::StrictlyLess);
+        assert_same(cond("x < 0").lhs, var("x"));
+        assert_same(cond("x <= 5").lhs, x_minus(6));
+        assert_eq!(cond("x <= 5").operator, ConditionOperator::StrictlyLess);
+        assert_same(cond("x > 0").lhs, x_minus(1));
+        assert_eq!(cond("x > 0").operator, ConditionOperator::GreaterOrEqual);
+        assert_same(cond("x >= 3").lhs, x_minus(3));
+        assert_eq!(cond("x >= 3").operator, ConditionOperator::GreaterOrEqual);
+        assert_eq!(cond("x != 0").operator, ConditionOperator::NotEqual);
+        assert_eq!(cond("x = 0").operator, ConditionOperator::Equal);
+        assert_same(cond("x <= -1"), cond("x < 0"));
+        // the nodes added by the normal form are told apart from the ones of the source
+        assert!(cond("x <= y").lhs.span().desugared);
+        assert!(!cond("x - y < 0").lhs.span().desugared);
+    }
+
+    #[test]
+    fn comparisons_between_expressions() {
+        // x <= y is x - (y + 1) < 0
+        assert_same(
+            cond("x <= y").lhs,
+            op(var("x"), Operator::Sub, op(var("y"), Operator::Add, int(1))),
+        );
+        assert_eq!(cond("x > y").operator, ConditionOperator::GreaterOrEqual);
+        assert_same(cond("x != y"), !cond("x = y"));
+        assert_same(cond("x >= y"), !cond("x < y"));
+    }
+
+    fn or(lhs: BooleanExp<'static>, rhs: BooleanExp<'static>) -> BooleanExp<'static> {
+        BooleanExp::Or {
+            lhs: Box::new(lhs),
+            rhs: Box::new(rhs),
+            span: Span::default(),
+        }
+    }
+
+    fn and(lhs: BooleanExp<'static>, rhs: BooleanExp<'static>) -> BooleanExp<'static> {
+        BooleanExp::And {
+            lhs: Box::new(lhs),
+            rhs: Box::new(rhs),
+            span: Span::default(),
+        }
+    }
+
+    #[test]
+    fn disjunction_precedence() {
+        let x_neg = || bexp("x < 0");
+        let x_big = || bexp("x > 10");
+        let y_neg = || bexp("y < 0");
+        assert_same(bexp("x < 0 | x > 10"), or(x_neg(), x_big()));
+        assert_same(bexp("x < 0 || x > 10"), bexp("x < 0 | x > 10"));
+        // & binds tighter than |
+        assert_same(
+            bexp("x < 0 & y < 0 | x > 10"),
+            or(and(x_neg(), y_neg()), x_big()),
+        );
+        assert_same(
+            bexp("x > 10 | x < 0 & y < 0"),
+            or(x_big(), and(x_neg(), y_neg())),
+        );
+        // | is left associative
+        assert_same(
+            bexp("x < 0 | y < 0 | x > 10"),
+            or(or(x_neg(), y_neg()), x_big()),
+        );
+        // ! binds tighter than both
+        assert_same(bexp("!x < 0 | y < 0"), or(bexp("x >= 0"), y_neg()));
+    }
+
+    #[test]
+    fn disjunction_de_morgan() {
+        assert_same(
+            bexp("!(x < 0 | x > 10)"),
+            and(bexp("x >= 0"), bexp("x <= 10")),
+        );
+        assert_same(bexp("!(x < 0 & y < 0)"), or(bexp("x >= 0"), bexp("y >= 0")));
+        assert_same(bexp("!!(x < 0 | y < 0)"), bexp("x < 0 | y < 0"));
+    }
+
+    fn aexp(src: &'static str) -> Box<ArithmeticExp<'static>> {
+        ArithmeticExpParser::new()
+            .parse(src, &mut vec![], Lexer::new(src))
+            .unwrap()
+    }
+
+    fn neg(exp: Box<ArithmeticExp<'static>>) -> Box<ArithmeticExp<'static>> {
+        Box::new(ArithmeticExp::Negation(exp, Span::default()))
+    }
+
+    #[test]
+    fn unary_minus() {
+        assert_same(aexp("-3"), int(-3));
+        assert_same(aexp("-(3)"), int(-3));
+        assert_same(aexp("-x"), neg(var("x")));
+        assert_same(aexp("--x"), neg(neg(var("x"))));
+        assert_same(aexp("-(a + b)"), neg(op(var("a"), Operator::Add, var("b"))));
+        // unary minus binds tighter than the binary operators
+        assert_same(aexp("-x * y"), op(neg(var("x")), Operator::Mul, var("y")));
+        assert_same(aexp("x - -y"), op(var("x"), Operator::Sub, neg(var("y"))));
+        assert_same(cond("-x < 0").lhs, neg(var("x")));
+    }
+
+    #[test]
+    fn modulo_precedence() {
+        assert_same(aexp("x % 2"), op(var("x"), Operator::Mod, int(2)));
+        // % binds like * and /, from left to right
+        assert_same(
+            aexp("i + 1 % n"),
+            op(var("i"), Operator::Add, op(int(1), Operator::Mod, var("n"))),
+        );
+        assert_same(
+            aexp("x * y % 3"),
+            op(op(var("x"), Operator::Mul, var("y")), Operator::Mod, int(3)),
+        );
+        assert_same(cond("x % 2 = 0").lhs, op(var("x"), Operator::Mod, int(2)));
+    }
+
+    #[test]
+    fn nondeterminism() {
+        assert_same(
+            aexp("rand(-5, 10)"),
+            Box::new(ArithmeticExp::Random {
+                low: -5,
+                upper: 10,
+                span: Span::default(),
+            }),
+        );
+        assert_same(
+            aexp("x + ?"),
+            op(
+                var("x"),
+                Operator::Add,
+                Box::new(ArithmeticExp::Unknown(Span::default())),
+            ),
+        );
+
+        let src = "either { x := 1 } or { x := ? }; skip";
+        let program = StatementParser::new()
+            .parse(src, &mut vec![], Lexer::new(src))
+            .unwrap();
+        let assign = |value| {
+            Box::new(Statement::Assignment(Assignment {
+                var: "x",
+                value,
+                span: Span::default(),
+            }))
+        };
+        assert_same(
+            program,
+            Box::new(Statement::Composition {
+                lhs: Box::new(Statement::Either {
+                    lhs: assign(int(1)),
+                    rhs: assign(Box::new(ArithmeticExp::Unknown(Span::default()))),
+                    span: Span::default(),
+                }),
+                rhs: Box::new(Statement::Skip(Span::default())),
+                span: Span::default(),
+            }),
+        );
+    }
+
+    #[test]
+    fn assume_assert() {
+        let src = "assume x >= 0;\n  assert x != 1";
+        let program = StatementParser::new()
+            .parse(src, &mut vec![], Lexer::new(src))
+            .unwrap();
+        assert_same(
+            &program,
+            &Box::new(Statement::Composition {
+                lhs: Box::new(Statement::Assume {
+                    guard: Box::new(bexp("x >= 0")),
+                    span: Span::default(),
+                }),
+                rhs: Box::new(Statement::Assert {
+                    guard: Box::new(bexp("x != 1")),
+                    span: Span::default(),
+                }),
+                span: Span::default(),
+            }),
+        );
+        let Statement::Composition { rhs, .. } = *program else {
+            panic!("expected a composition")
+        };
+        let Statement::Assert { span, .. } = *rhs else {
+            panic!("expected an assertion")
+        };
+        assert_eq!(span.start, Position { line: 1, clm: 2 });
+    }
+
+    #[test]
+    fn spans() {
+        let at = |line, clm| Position { line, clm };
+        let src = "x := 1;\nwhile x < 10 do {\n  x := x + 1\n}";
+        let program = StatementParser::new()
+            .parse(src, &mut vec![], Lexer::new(src))
+            .unwrap();
+        let Statement::Composition { lhs, rhs, span } = *program else {
+            panic!("expected a composition")
+        };
+        assert_eq!((span.start, span.end), (at(0, 0), at(3, 1)));
+        let Statement::Assignment(Assignment { span, .. }) = *lhs else {
+            panic!("expected an assignment")
+        };
+        assert_eq!((span.start, span.end), (at(0, 0), at(0, 6)));
+        let Statement::While { guard, body, span } = *rhs else {
+            panic!("expected a loop")
+        };
+        assert_eq!((span.start, span.end), (at(1, 0), at(3, 1)));
+        let BooleanExp::ArithmeticCondition(ArithmeticCondition { span, .. }) = *guard else {
+            panic!("expected a comparison")
+        };
+        assert_eq!((span.start, span.end), (at(1, 6), at(1, 12)));
+        let Statement::Assignment(Assignment { value, span, .. }) = *body else {
+            panic!("expected an assignment")
+        };
+        assert_eq!((span.start, span.end), (at(2, 2), at(2, 12)));
+        assert_eq!(
+            (value.span().start.clone(), value.span().end.clone()),
+            (at(2, 7), at(2, 12))
+        );
+        let ArithmeticExp::BinaryOperation { rhs, .. } = *value else {
+            panic!("expected an addition")
+        };
+        assert_eq!(
+            (rhs.span().start.clone(), rhs.span().end.clone()),
+            (at(2, 11), at(2, 12))
+        );
+    }
+}
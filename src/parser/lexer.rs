@@ -24,8 +24,7 @@ impl<'input> Iterator for Lexer<'input> {
     fn next(&mut self) -> Option<Self::Item> {
         self.token_stream.next().map(|(token, span)| match token {
             Ok(token) => Ok((span.start, token, span.end)),
-            Err(_) => Ok((span.start, Token::Error, span.end)),
-            // or specify your lexical error to parse error
+            Err(err) => Ok((span.start, Token::Error(err), span.end)),
         })
     }
 }
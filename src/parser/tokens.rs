@@ -1,16 +1,22 @@
-use logos::{Lexer, Logos, Skip};
+use logos::Logos;
 use std::fmt;
 use std::num::ParseIntError;
 use std::str::ParseBoolError;
 
-use super::ast::Position;
-
+#[allow(clippy::enum_variant_names)]
 #[derive(Default, Debug, Clone, PartialEq)]
 pub enum LexicalError {
     InvalidInteger(ParseIntError),
     InvalidBoolean(ParseBoolError),
     #[default]
     InvalidToken,
+    // rand(low, upper) with low > upper has no value, with the byte range of the rand
+    EmptyRandom {
+        low: i64,
+        upper: i64,
+        start: usize,
+        end: usize,
+    },
 }
 
 impl From<ParseIntError> for LexicalError {
@@ -25,22 +31,22 @@ impl From<ParseBoolError> for LexicalError {
     }
 }
 
-fn newline_callback<'a>(lex: &mut Lexer<'a, Token<'a>>) -> Skip {
-    lex.extras.0 += 1;
-    lex.extras.1 = lex.span().end;
-    Skip
-}
-
-fn get_while_token_pos<'a>(lex: &mut Lexer<'a, Token<'a>>) -> Position {
-    Position {
-        line: lex.extras.0,
-        clm: lex.span().start - lex.extras.1,
+impl fmt::Display for LexicalError {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        match self {
+            LexicalError::InvalidInteger(err) => write!(f, "invalid integer literal: {err}"),
+            LexicalError::InvalidBoolean(err) => write!(f, "invalid boolean literal: {err}"),
+            LexicalError::InvalidToken => write!(f, "unknown character"),
+            LexicalError::EmptyRandom { low, upper, .. } => write!(
+                f,
+                "empty range in rand({low}, {upper}), the lower bound is greater than the upper bound"
+            ),
+        }
     }
 }
 
 #[derive(Logos, Clone, Debug, PartialEq)]
-#[logos(skip r"[ \t\f]+", skip r"assume[^\n]*", skip r"#[^\n]*", error = LexicalError)]
-#[logos(extras=(usize, usize))]
+#[logos(skip r"[ \t\f\n]+", skip r"#[^\n]*", error = LexicalError)]
 pub enum Token<'input> {
     #[regex("[_a-zA-Z][_0-9a-zA-Z]*", |lex| lex.slice())]
     Identifier(&'input str),
@@ -52,12 +58,24 @@ pub enum Token<'input> {
     Then,
     #[token("else")]
     Else,
-    #[token("while", get_while_token_pos)]
-    While(Position),
+    #[token("while")]
+    While,
     #[token("do")]
     Do,
     #[token("skip")]
     Skip,
+    #[token("assume")]
+    Assume,
+    #[token("assert")]
+    Assert,
+    #[token("either")]
+    Either,
+    #[token("or")]
+    EitherOr,
+    #[token("rand")]
+    Rand,
+    #[token("?")]
+    Unknown,
 
     #[token("{")]
     LCurlyBracket,
@@ -72,6 +90,8 @@ pub enum Token<'input> {
     Assign,
     #[token(";")]
     Semicolon,
+    #[token(",")]
+    Comma,
 
     #[token("+")]
     OperatorAdd,
@@ -81,22 +101,34 @@ pub enum Token<'input> {
     OperatorMul,
     #[token("/")]
     OperatorDiv,
+    #[token("%")]
+    OperatorMod,
 
     #[regex("true|false", |lex| lex.slice().parse())]
     Boolean(bool),
     #[token("=")]
     Equal,
+    #[token("!=")]
+    NotEqual,
     #[token("<")]
     StrictlyLess,
+    #[token("<=")]
+    LessOrEqual,
+    #[token(">")]
+    StrictlyGreater,
+    #[token(">=")]
+    GreaterOrEqual,
     #[token("!")]
     Not,
     #[token("&")]
     And,
+    #[token("|")]
+    #[token("||")]
+    Or,
 
-    #[regex(r"\n", newline_callback)]
-    Newline,
-
-    Error,
+    // handed to the parser instead of stopping the lexing, so that the errors after it are
+    // reported as well
+    Error(LexicalError),
 }
 
 impl<'input> fmt::Display for Token<'input> {
@@ -1,2 +1,3 @@
 pub mod node;
+#[allow(clippy::module_inception)]
 pub mod propagation_algo;
@@ -1,7 +1,7 @@
 use std::{cell::RefCell, collections::HashMap, rc::Rc};
 
 use crate::{
-    abstract_domains::abstract_domain::AbstractDomain,
+    abstract_domains::abstract_domain::{AbstractDomain, IntervalBound},
     parser::ast::{ArithmeticExp, Operator},
     state::State,
 };
@@ -13,6 +13,11 @@ pub enum Node<D: AbstractDomain> {
         left: Rc<Node<D>>,
         right: Rc<Node<D>>,
     },
+    // 0 - operand
+    Negation {
+        value: RefCell<D>,
+        operand: Rc<Node<D>>,
+    },
     VarLeaf {
         value: RefCell<D>,
     },
@@ -28,17 +33,38 @@ impl<D: AbstractDomain> Node<D> {
         var_leafs: &mut HashMap<&'a str, Rc<Self>>,
     ) -> Rc<Self> {
         match exp {
-            ArithmeticExp::Integer(c) => Rc::new(Node::ConstantLeaf {
+            ArithmeticExp::Integer(c, _) => Rc::new(Node::ConstantLeaf {
                 value: D::constant_abstraction(*c),
             }),
-            ArithmeticExp::Variable(var) => {
+            // each occurrence of an input is a fresh value, which no refinement can be kept on
+            ArithmeticExp::Random {
+                low,
+                upper,
+                span: _,
+            } => Rc::new(Node::ConstantLeaf {
+                value: D::interval_abstraction(
+                    IntervalBound::Num(*low),
+                    IntervalBound::Num(*upper),
+                ),
+            }),
+            ArithmeticExp::Unknown(_) => Rc::new(Node::ConstantLeaf { value: D::top() }),
+            ArithmeticExp::Variable(var, _) => {
                 let node = Rc::new(Node::VarLeaf {
                     value: RefCell::new(*state.lookup(var)),
                 });
                 var_leafs.insert(var, Rc::clone(&node));
                 node
             }
-            ArithmeticExp::BinaryOperation { lhs, operator, rhs } => Rc::new(Node::Internal {
+            ArithmeticExp::Negation(exp, _) => Rc::new(Node::Negation {
+                value: RefCell::new(D::top()),
+                operand: Self::build(exp, state, var_leafs),
+            }),
+            ArithmeticExp::BinaryOperation {
+                lhs,
+                operator,
+                rhs,
+                span: _,
+            } => Rc::new(Node::Internal {
                 value: RefCell::new(D::top()),
                 operator: *operator,
                 left: Self::build(lhs, state, var_leafs),
@@ -47,7 +73,9 @@ impl<D: AbstractDomain> Node<D> {
         }
     }
 
-    pub fn forward_analysis(&self) {
+    // the values are normalized in the context of the analysis, so that the local iterations
+    // stay in the domain
+    pub fn forward_analysis(&self, ctx: &D::Context) {
         match self {
             Node::Internal {
                 value,
@@ -60,16 +88,22 @@ impl<D: AbstractDomain> Node<D> {
                     Operator::Sub => D::sub,
                     Operator::Mul => D::mul,
                     Operator::Div => D::div,
+                    Operator::Mod => D::rem,
                 };
-                left.forward_analysis();
-                right.forward_analysis();
-                *value.borrow_mut() = op(left.get_value(), right.get_value());
+                left.forward_analysis(ctx);
+                right.forward_analysis(ctx);
+                *value.borrow_mut() = op(left.get_value(), right.get_value()).normalize(ctx);
+            }
+            Node::Negation { value, operand } => {
+                operand.forward_analysis(ctx);
+                *value.borrow_mut() =
+                    (D::constant_abstraction(0) - operand.get_value()).normalize(ctx);
             }
             _ => (),
         }
     }
 
-    pub fn backward_analysis(&self, refinement: D) -> bool {
+    pub fn backward_analysis(&self, refinement: D, ctx: &D::Context) -> bool {
         match self {
             Node::Internal {
                 value,
@@ -86,7 +120,16 @@ impl<D: AbstractDomain> Node<D> {
                     *operator,
                 );
 
-                left.backward_analysis(refs[0]) && right.backward_analysis(refs[1])
+                left.backward_analysis(refs[0].normalize(ctx), ctx)
+                    && right.backward_analysis(refs[1].normalize(ctx), ctx)
+            }
+            // -x = r => x = -r
+            Node::Negation { value, operand } => {
+                *value.borrow_mut() = refinement;
+                let operand_ref = operand
+                    .get_value()
+                    .glb(&(D::constant_abstraction(0) - refinement));
+                operand.backward_analysis(operand_ref.normalize(ctx), ctx)
             }
             Node::ConstantLeaf { value } => refinement.glb(value) != D::bottom(),
             Node::VarLeaf { value } => {
@@ -108,6 +151,7 @@ impl<D: AbstractDomain> Node<D> {
                 left: _,
                 right: _,
             }
+            | Node::Negation { value, operand: _ }
             | Node::VarLeaf { value } => *value.borrow(),
         }
     }
@@ -124,7 +168,12 @@ impl<D: AbstractDomain> Node<D> {
                 Operator::Sub => "-".to_string(),
                 Operator::Mul => "*".to_string(),
                 Operator::Div => "/".to_string(),
+                Operator::Mod => "%".to_string(),
             },
+            Node::Negation {
+                value: _,
+                operand: _,
+            } => "-".to_string(),
             Node::ConstantLeaf { value: _ } => "Const".to_string(),
             Node::VarLeaf { value: _ } => "Var".to_string(),
         };
@@ -139,17 +188,17 @@ impl<D: AbstractDomain> Node<D> {
             new_indent = format!("{indent}   ");
         }
 
-        match self {
-            Node::Internal {
-                value: _,
-                operator: _,
-                left,
-                right,
-            } => {
-                left.inner_pretty_print(new_indent.clone(), false);
-                right.inner_pretty_print(new_indent, true);
-            }
-            _ => (),
+        if let Node::Internal {
+            value: _,
+            operator: _,
+            left,
+            right,
+        } = self
+        {
+            left.inner_pretty_print(new_indent.clone(), false);
+            right.inner_pretty_print(new_indent, true);
+        } else if let Node::Negation { value: _, operand } = self {
+            operand.inner_pretty_print(new_indent, true);
         }
     }
 
@@ -40,12 +40,11 @@ impl<'a, 'b, D: AbstractDomain> PropagationAlgorithm<'a, 'b, D> {
         let gt = D::interval_abstraction(IntervalBound::Num(0), IntervalBound::PosInf);
         let sgt = D::interval_abstraction(IntervalBound::Num(1), IntervalBound::PosInf);
 
-        let slice = &match self.cond {
+        // the values excluded by != are known only after the forward analysis
+        let slice = |value: D| match self.cond {
             ConditionOperator::Equal => D::constant_abstraction(0),
             // eventually discard 0 if it is a bound
-            ConditionOperator::NotEqual => stl
-                .glb(&self.tree.get_value())
-                .lub(&sgt.glb(&self.tree.get_value())),
+            ConditionOperator::NotEqual => stl.glb(&value).lub(&sgt.glb(&value)),
             ConditionOperator::StrictlyLess => stl,
             ConditionOperator::GreaterOrEqual => gt,
         };
@@ -55,15 +54,16 @@ impl<'a, 'b, D: AbstractDomain> PropagationAlgorithm<'a, 'b, D> {
         let mut fixpoint = false;
         let mut satisfiable = true;
         while satisfiable && !fixpoint {
-            self.tree.forward_analysis();
+            self.tree.forward_analysis(self.state.context());
 
             println!("After forward analysis");
             self.tree.pretty_print();
 
             let prev: HashMap<&str, D> = clone_var_leafs();
-            satisfiable = self
-                .tree
-                .backward_analysis(self.tree.get_value().glb(slice));
+            satisfiable = self.tree.backward_analysis(
+                self.tree.get_value().glb(&slice(self.tree.get_value())),
+                self.state.context(),
+            );
 
             println!("After backward analysis");
             self.tree.pretty_print();
@@ -0,0 +1,620 @@
+use std::{
+    collections::{BTreeMap, HashSet},
+    path::Path,
+};
+
+use serde_json::{Value, json};
+
+use crate::{
+    abstract_domains::{abstract_domain::AbstractDomain, int::Int, interval::Bounds},
+    abstract_states::abstract_state::AbstractState,
+    interpreter::{
+        IntegerType, Interpreter, Machine, OverflowBehavior, Point, ProgramInvariants, Verdict,
+    },
+    parser::ast::{Position, Span, Statement},
+};
+
+// how the program was analyzed, as given on the command line and in the environment
+pub struct Configuration<'a> {
+    pub domain: &'a str,
+    // only for the domains built on intervals
+    pub bounds: Option<Bounds>,
+    // the mathematical integers when there is none
+    pub machine: Option<Machine>,
+}
+
+// 1-based, like the locations of the diagnostics
+fn location(pos: &Position) -> Value {
+    json!({ "line": pos.line + 1, "column": pos.clm + 1 })
+}
+
+// the infinite bounds are the strings "-inf" and "inf", like in the values of the variables
+fn bound(value: Int) -> Value {
+    match value {
+        Int::Num(x) => json!(x),
+        Int::NegInf | Int::PosInf => json!(String::from(value)),
+    }
+}
+
+// a program point with the value of every variable there
+fn program_point<'a, S: AbstractState<'a>>(
+    kind: &str,
+    pos: &Position,
+    state: &S,
+    vars: &HashSet<&'a str>,
+) -> Value {
+    let values: BTreeMap<_, _> = vars
+        .iter()
+        .map(|var| {
+            // a bottom state may not know its variables
+            let value = match state.is_bottom() {
+                true => S::Value::bottom(),
+                false => state.value_of(var),
+            };
+            (*var, Into::<String>::into(value))
+        })
+        .collect();
+    json!({
+        "kind": kind,
+        "location": location(pos),
+        "reachable": !state.is_bottom(),
+        "variables": values,
+    })
+}
+
+// the invariants are the ones returned by the interpreter, the last one holds at the end of the
+// program
+pub fn to_json<'a, S: AbstractState<'a>>(
+    config: &Configuration,
+    program: &Statement<'a>,
+    interpreter: &Interpreter<'a, S>,
+    invariants: &ProgramInvariants<S>,
+) -> String {
+    let mut vars = HashSet::new();
+    program.extract_vars(&mut vars);
+
+    let mut points: Vec<(Position, Value)> = vec![];
+    for (pos, state) in invariants.iter().take(invariants.len().saturating_sub(1)) {
+        points.push((pos.clone(), program_point("loop-head", pos, state, &vars)));
+    }
+    for ((pos, point), state) in interpreter.annotations() {
+        let kind = match point {
+            Point::Then => "then",
+            Point::Else => "else",
+            Point::After => "after",
+            Point::LoopExit => "loop-exit",
+        };
+        points.push((pos.clone(), program_point(kind, pos, state, &vars)));
+    }
+    if let Some((_, state)) = invariants.last_key_value() {
+        let end = &program.span().end;
+        points.push((end.clone(), program_point("end", end, state, &vars)));
+    }
+    // in the order of the source, sorting is stable so the end of the program stays last
+    points.sort_by(|(lhs, _), (rhs, _)| lhs.cmp(rhs));
+
+    let mut thresholds: Vec<_> = interpreter.widening_thresholds().iter().collect();
+    thresholds.sort();
+    let loops: Vec<_> = interpreter
+        .loop_iterations()
+        .iter()
+        .map(|(pos, iterations)| {
+            json!({
+                "location": location(pos),
+                "analyses": iterations.analyses,
+                "widening_iterations": iterations.widening,
+                "narrowing_iterations": iterations.narrowing,
+            })
+        })
+        .collect();
+
+    let integers = config.machine.map(|machine| match machine.integers {
+        IntegerType::I64 => "i64",
+        IntegerType::I32 => "i32",
+        IntegerType::U32 => "u32",
+    });
+    let overflow = config.machine.map(|machine| match machine.overflow {
+        OverflowBehavior::Wrap => "wrap",
+        OverflowBehavior::Trap => "trap",
+    });
+    let report = json!({
+        "configuration": {
+            "domain": config.domain,
+            // the domains that are not built on intervals have no bounds
+            "m": config.bounds.map_or(Value::Null, |bounds| bound(bounds.m())),
+            "n": config.bounds.map_or(Value::Null, |bounds| bound(bounds.n())),
+            "integers": integers,
+            "overflow": overflow,
+            "narrowing_steps": interpreter.narrowing_steps(),
+            "widening_thresholds": thresholds,
+        },
+        "program_points": points.into_iter().map(|(_, point)| point).collect::<Vec<_>>(),
+        "loops": loops,
+    });
+    serde_json::to_string_pretty(&report).unwrap()
+}
+
+// the kinds of alarms, each one is a rule of the SARIF report
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub enum Rule {
+    Assertion,
+    DivisionByZero,
+    Overflow,
+    DeadCode,
+    ConstantGuard,
+}
+
+impl Rule {
+    const ALL: [Rule; 5] = [
+        Rule::Assertion,
+        Rule::DivisionByZero,
+        Rule::Overflow,
+        Rule::DeadCode,
+        Rule::ConstantGuard,
+    ];
+
+    fn id(&self) -> &'static str {
+        match self {
+            Rule::Assertion => "assertion",
+            Rule::DivisionByZero => "div-by-zero",
+            Rule::Overflow => "overflow",
+            Rule::DeadCode => "dead-code",
+            Rule::ConstantGuard => "constant-guard",
+        }
+    }
+
+    // how the alarms are introduced on the command line
+    pub fn label(&self) -> &'static str {
+        match self {
+            Rule::Assertion => "Assertion",
+            Rule::DivisionByZero => "Division",
+            Rule::Overflow => "Overflow",
+            Rule::DeadCode => "Dead code",
+            Rule::ConstantGuard => "Constant guard",
+        }
+    }
+
+    fn description(&self) -> &'static str {
+        match self {
+            Rule::Assertion => "An assertion may be violated",
+            Rule::DivisionByZero => "A divisor may be zero",
+            Rule::Overflow => "An integer operation may overflow",
+            Rule::DeadCode => "A statement is never executed",
+            Rule::ConstantGuard => "A guard is always true or always false",
+        }
+    }
+}
+
+// a possible error found by the analysis, definite when every trace reaching it is an error
+#[derive(Clone, Debug)]
+pub struct Alarm {
+    pub rule: Rule,
+    pub definite: bool,
+    pub message: String,
+    pub span: Span,
+}
+
+// a division is checked like the assertion that its divisor is not zero
+pub fn division_verdict(verdict: Verdict) -> &'static str {
+    match verdict {
+        Verdict::Proven => "safe",
+        Verdict::PossiblyViolated => "possibly by zero",
+        Verdict::DefinitelyViolated => "definitely by zero",
+    }
+}
+
+// an operation is checked like the assertion that its result fits in the machine integers
+pub fn overflow_verdict(verdict: Verdict) -> &'static str {
+    match verdict {
+        Verdict::Proven => "no overflow",
+        Verdict::PossiblyViolated => "possibly overflows",
+        Verdict::DefinitelyViolated => "definitely overflows",
+    }
+}
+
+// the statements never executed, only the outermost ones, and the guards that always go the same
+// way, in the order of the source
+pub fn dead_code<'a, S: AbstractState<'a>>(interpreter: &Interpreter<'a, S>) -> Vec<Alarm> {
+    let mut alarms = vec![];
+    find_dead_code(interpreter.program(), interpreter, &mut alarms);
+    alarms
+}
+
+fn find_dead_code<'a, S: AbstractState<'a>>(
+    stmt: &Statement<'a>,
+    interpreter: &Interpreter<'a, S>,
+    alarms: &mut Vec<Alarm>,
+) {
+    let alarm = |rule, message: &str, span: &Span| Alarm {
+        rule,
+        definite: false,
+        message: message.to_string(),
+        span: span.clone(),
+    };
+    match stmt {
+        Statement::Composition { lhs, rhs, span: _ } => {
+            find_dead_code(lhs, interpreter, alarms);
+            find_dead_code(rhs, interpreter, alarms);
+        }
+        _ if !interpreter.reached(stmt) => alarms.push(alarm(
+            Rule::DeadCode,
+            "statement never executed",
+            stmt.span(),
+        )),
+        Statement::Either { lhs, rhs, span: _ } => {
+            find_dead_code(lhs, interpreter, alarms);
+            find_dead_code(rhs, interpreter, alarms);
+        }
+        Statement::Conditional {
+            guard,
+            true_branch,
+            false_branch,
+            span: _,
+        } => {
+            if let Some(outcomes) = interpreter.guards().get(&guard.span().start) {
+                match (outcomes.holds, outcomes.fails) {
+                    (true, false) => alarms.push(alarm(
+                        Rule::ConstantGuard,
+                        "guard always true",
+                        guard.span(),
+                    )),
+                    (false, true) => alarms.push(alarm(
+                        Rule::ConstantGuard,
+                        "guard always false",
+                        guard.span(),
+                    )),
+                    _ => (),
+                }
+            }
+            find_dead_code(true_branch, interpreter, alarms);
+            find_dead_code(false_branch, interpreter, alarms);
+        }
+        Statement::While { guard, body, span } => {
+            if let Some(outcomes) = interpreter.guards().get(&guard.span().start)
+                && !outcomes.holds
+            {
+                alarms.push(alarm(Rule::ConstantGuard, "loop body never executed", span));
+            }
+            find_dead_code(body, interpreter, alarms);
+        }
+        _ => (),
+    }
+}
+
+// in the order of the source
+pub fn alarms<'a, S: AbstractState<'a>>(interpreter: &Interpreter<'a, S>) -> Vec<Alarm> {
+    let divisions = interpreter.divisions().values().map(|division| {
+        let message = format!("division {}", division_verdict(division.verdict));
+        (Rule::DivisionByZero, division, message)
+    });
+    let assertions = interpreter.assertions().values().map(|assertion| {
+        let message = format!("assertion {}", assertion.verdict);
+        (Rule::Assertion, assertion, message)
+    });
+    let overflows = interpreter.overflows().values().map(|overflow| {
+        let message = format!("operation {}", overflow_verdict(overflow.verdict));
+        (Rule::Overflow, overflow, message)
+    });
+    let mut alarms: Vec<_> = divisions
+        .chain(assertions)
+        .chain(overflows)
+        .filter(|(_, check, _)| check.verdict != Verdict::Proven)
+        .map(|(rule, check, message)| Alarm {
+            rule,
+            definite: check.verdict == Verdict::DefinitelyViolated,
+            message,
+            span: check.span.clone(),
+        })
+        .chain(dead_code(interpreter))
+        .collect();
+    alarms.sort_by(|lhs, rhs| lhs.span.start.cmp(&rhs.span.start));
+    alarms
+}
+
+// the path as a URI reference, with its other characters percent-encoded
+fn uri(path: &Path) -> String {
+    path.to_string_lossy()
+        .replace('\\', "/")
+        .bytes()
+        .map(|byte| match byte {
+            b'A'..=b'Z' | b'a'..=b'z' | b'0'..=b'9' | b'-' | b'.' | b'_' | b'~' | b'/' => {
+                (byte as char).to_string()
+            }
+            byte => format!("%{byte:02X}"),
+        })
+        .collect()
+}
+
+// the file relative to the root of the sources when it is below it, else its absolute URI
+fn artifact_location(file: &Path, root: &Path) -> Value {
+    let file = file.strip_prefix(root).unwrap_or(file);
+    match file.is_absolute() {
+        true => json!({ "uri": format!("file://{}", uri(file)) }),
+        false => json!({ "uri": uri(file), "uriBaseId": "SRCROOT" }),
+    }
+}
+
+// SARIF 2.1.0 log of the alarms found in file, the relative paths are below root
+pub fn to_sarif(file: &str, root: &Path, alarms: &[Alarm]) -> String {
+    let rules: Vec<_> = Rule::ALL
+        .iter()
+        .map(|rule| {
+            json!({
+                "id": rule.id(),
+                "shortDescription": { "text": rule.description() },
+                "defaultConfiguration": { "level": "warning" },
+            })
+        })
+        .collect();
+    let results: Vec<_> = alarms
+        .iter()
+        .map(|alarm| {
+            json!({
+                "ruleId": alarm.rule.id(),
+                "ruleIndex": Rule::ALL.iter().position(|rule| *rule == alarm.rule),
+                "level": if alarm.definite { "error" } else { "warning" },
+                "message": { "text": alarm.message },
+                "locations": [{
+                    "physicalLocation": {
+                        "artifactLocation": artifact_location(Path::new(file), root),
+                        "region": {
+                            "startLine": alarm.span.start.line + 1,
+                            "startColumn": alarm.span.start.clm + 1,
+                            "endLine": alarm.span.end.line + 1,
+                            "endColumn": alarm.span.end.clm + 1,
+                        },
+                    },
+                }],
+            })
+        })
+        .collect();
+
+    let log = json!({
+        "$schema": "https://json.schemastore.org/sarif-2.1.0.json",
+        "version": "2.1.0",
+        "runs": [{
+            "tool": {
+                "driver": {
+                    "name": env!("CARGO_PKG_NAME"),
+                    "version": env!("CARGO_PKG_VERSION"),
+                    "rules": rules,
+                },
+            },
+            "originalUriBaseIds": {
+                "SRCROOT": { "uri": format!("file://{}/", uri(root).trim_end_matches('/')) },
+            },
+            // the columns are bytes, only the comments may not be ASCII and they end the line
+            "columnKind": "unicodeCodePoints",
+            "results": results,
+        }],
+    });
+    serde_json::to_string_pretty(&log).unwrap()
+}
+
+#[cfg(test)]
+mod test {
+    use std::path::Path;
+
+    use serde_json::{Value, json};
+
+    use crate::{
+        abstract_domains::{
+            int::Int,
+            interval::{Bounds, Interval},
+        },
+        abstract_states::abstract_state::AbstractState,
+        diagnostics::parse,
+        interpreter::{IntegerType, Interpreter, Machine, OverflowBehavior},
+        state::State,
+    };
+
+    use super::{
+        Configuration, Rule, alarms, artifact_location, dead_code, division_verdict,
+        overflow_verdict, to_json, to_sarif,
+    };
+
+    #[test]
+    fn json_report() {
+        let src = "x := 0;\nwhile x < 10 do\n  x := x + 1";
+        let program = parse(src).unwrap();
+        let mut interpreter = Interpreter::<State<Interval>>::build(&program, &Bounds::default())
+            .with_annotations(true);
+        let invariants = interpreter.interpret();
+        let config = Configuration {
+            domain: "interval",
+            bounds: Some(Bounds::default()),
+            machine: None,
+        };
+        let report: Value =
+            serde_json::from_str(&to_json(&config, &program, &interpreter, &invariants)).unwrap();
+
+        assert_eq!(report["configuration"]["domain"], "interval");
+        assert_eq!(report["configuration"]["m"], "-inf");
+        assert_eq!(report["configuration"]["n"], "inf");
+        assert_eq!(report["configuration"]["integers"], Value::Null);
+        assert_eq!(
+            report["configuration"]["widening_thresholds"],
+            json!([0, 1, 10])
+        );
+        assert_eq!(
+            report["loops"][0]["location"],
+            json!({"line": 2, "column": 1})
+        );
+        assert_eq!(report["loops"][0]["analyses"], 1);
+
+        let points = report["program_points"].as_array().unwrap();
+        let kinds: Vec<_> = points.iter().map(|p| p["kind"].as_str().unwrap()).collect();
+        assert_eq!(kinds, ["after", "loop-head", "after", "loop-exit", "end"]);
+        assert_eq!(points[1]["variables"]["x"], "[0,10]");
+        assert_eq!(points[4]["variables"]["x"], "[10,10]");
+        assert_eq!(points[4]["location"], json!({"line": 3, "column": 13}));
+
+        let bounded = Configuration {
+            bounds: Some(Bounds::new(Int::Num(-5), Int::PosInf)),
+            ..config
+        };
+        let report: Value =
+            serde_json::from_str(&to_json(&bounded, &program, &interpreter, &invariants)).unwrap();
+        assert_eq!(report["configuration"]["m"], -5);
+        assert_eq!(report["configuration"]["n"], "inf");
+        let signs = Configuration {
+            domain: "sign",
+            bounds: None,
+            machine: None,
+        };
+        let report: Value =
+            serde_json::from_str(&to_json(&signs, &program, &interpreter, &invariants)).unwrap();
+        assert_eq!(report["configuration"]["m"], Value::Null);
+    }
+
+    #[test]
+    fn sarif_report() {
+        let src = "x := rand(0, 5);\nassert x >= 0;\nassert x > 2;\nassert x > 9";
+        let program = parse(src).unwrap();
+        let mut interpreter = Interpreter::<State<Interval>>::build(&program, &Bounds::default());
+        interpreter.interpret();
+        let alarms = alarms(&interpreter);
+        let root = Path::new("/work/src");
+        let log: Value = serde_json::from_str(&to_sarif("a.toy", root, &alarms)).unwrap();
+
+        assert_eq!(log["version"], "2.1.0");
+        let run = &log["runs"][0];
+        assert_eq!(run["tool"]["driver"]["rules"][0]["id"], "assertion");
+        let results = run["results"].as_array().unwrap();
+        assert_eq!(results.len(), 2);
+        assert_eq!(results[0]["ruleId"], "assertion");
+        assert_eq!(results[0]["level"], "warning");
+        assert_eq!(results[1]["level"], "error");
+        assert_eq!(
+            results[0]["locations"][0]["physicalLocation"]["region"],
+            json!({"startLine": 3, "startColumn": 1, "endLine": 3, "endColumn": 13})
+        );
+        assert_eq!(run["columnKind"], "unicodeCodePoints");
+        assert_eq!(
+            run["originalUriBaseIds"]["SRCROOT"]["uri"],
+            "file:///work/src/"
+        );
+        assert_eq!(
+            results[0]["locations"][0]["physicalLocation"]["artifactLocation"],
+            json!({"uri": "a.toy", "uriBaseId": "SRCROOT"})
+        );
+
+        let location = |file| artifact_location(Path::new(file), root);
+        assert_eq!(
+            location("/work/src/tests/my loop.toy"),
+            json!({"uri": "tests/my%20loop.toy", "uriBaseId": "SRCROOT"})
+        );
+        assert_eq!(
+            location("/tmp/a#1.toy"),
+            json!({"uri": "file:///tmp/a%231.toy"})
+        );
+    }
+
+    #[test]
+    fn division_alarms() {
+        let src = "x := rand(-2, 5);\ny := 10 / (x + 3) + 10 / x;\nassume x = 0;\ny := y % x";
+        let program = parse(src).unwrap();
+        let mut interpreter = Interpreter::<State<Interval>>::build(&program, &Bounds::default());
+        interpreter.interpret();
+        let verdicts: Vec<_> = interpreter
+            .divisions()
+            .values()
+            .map(|division| division_verdict(division.verdict))
+            .collect();
+        assert_eq!(verdicts, ["safe", "possibly by zero", "definitely by zero"]);
+
+        let alarms = alarms(&interpreter);
+        assert_eq!(alarms.len(), 2);
+        assert_eq!(alarms[0].rule, Rule::DivisionByZero);
+        assert!(!alarms[0].definite);
+        assert_eq!((alarms[0].span.start.clm, alarms[0].span.end.clm), (20, 26));
+        assert!(alarms[1].definite);
+    }
+
+    #[test]
+    fn overflow_alarms() {
+        let src = "x := 2147483647;\ny := x + 1;\nz := rand(0, 10) * 2;\nw := ? - 1";
+        let program = parse(src).unwrap();
+        let machine = |overflow| Machine {
+            integers: IntegerType::I32,
+            overflow,
+        };
+
+        let mut interpreter = Interpreter::<State<Interval>>::build(&program, &Bounds::default())
+            .with_machine(Some(machine(OverflowBehavior::Wrap)));
+        let invariants = interpreter.interpret();
+        let verdicts: Vec<_> = interpreter
+            .overflows()
+            .values()
+            .map(|overflow| overflow_verdict(overflow.verdict))
+            .collect();
+        assert_eq!(
+            verdicts,
+            ["definitely overflows", "no overflow", "possibly overflows"]
+        );
+        let end = invariants.last_key_value().unwrap().1;
+        let (min, max) = (i32::MIN.into(), i32::MAX.into());
+        assert_eq!(end.lookup("y"), &Interval::from([min, min]));
+        assert_eq!(end.lookup("w"), &Interval::from([min, max]));
+
+        let alarms = alarms(&interpreter);
+        assert_eq!(alarms.len(), 2);
+        assert_eq!(alarms[0].rule, Rule::Overflow);
+        assert!(alarms[0].definite);
+        assert_eq!((alarms[0].span.start.clm, alarms[0].span.end.clm), (5, 10));
+
+        // the traces stop at the first overflow
+        let mut interpreter = Interpreter::<State<Interval>>::build(&program, &Bounds::default())
+            .with_machine(Some(machine(OverflowBehavior::Trap)));
+        let invariants = interpreter.interpret();
+        assert!(invariants.last_key_value().unwrap().1.is_bottom());
+        assert_eq!(interpreter.overflows().len(), 1);
+    }
+
+    #[test]
+    fn dead_code_report() {
+        let src = "x := rand(0, 5);\nif x < 10 then y := 1 else y := 2;\nwhile x > 7 do\n  x := x - 1;\nassert x > 9;\nz := 1;\nw := 2";
+        let program = parse(src).unwrap();
+        let mut interpreter = Interpreter::<State<Interval>>::build(&program, &Bounds::default());
+        interpreter.interpret();
+        let found: Vec<_> = dead_code(&interpreter)
+            .into_iter()
+            .map(|alarm| (alarm.rule, alarm.message, alarm.span.start.line))
+            .collect();
+        assert_eq!(
+            found,
+            [
+                (Rule::ConstantGuard, "guard always true".to_string(), 1),
+                (Rule::DeadCode, "statement never executed".to_string(), 1),
+                (
+                    Rule::ConstantGuard,
+                    "loop body never executed".to_string(),
+                    2
+                ),
+                (Rule::DeadCode, "statement never executed".to_string(), 3),
+                (Rule::DeadCode, "statement never executed".to_string(), 5),
+                (Rule::DeadCode, "statement never executed".to_string(), 6),
+            ]
+        );
+        assert_eq!(alarms(&interpreter).len(), 7);
+    }
+
+    #[test]
+    fn dead_code_either() {
+        let found = |src| {
+            let program = parse(src).unwrap();
+            let mut interpreter =
+                Interpreter::<State<Interval>>::build(&program, &Bounds::default());
+            interpreter.interpret();
+            dead_code(&interpreter)
+                .into_iter()
+                .map(|alarm| (alarm.rule, alarm.span.start.line))
+                .collect::<Vec<_>>()
+        };
+        // an either never executed is dead code as a whole, not branch by branch
+        let src = "x := 0;\nassume x > 0;\neither { y := 1 } or { y := 2 }";
+        assert_eq!(found(src), [(Rule::DeadCode, 2)]);
+        let src = "x := 0;\neither {\n  assume x > 0;\n  y := 1\n} or { y := 2 }";
+        assert_eq!(found(src), [(Rule::DeadCode, 3)]);
+    }
+}
@@ -1,11 +1,24 @@
 use core::fmt;
 use std::collections::{HashMap, HashSet};
 
-use crate::abstract_domains::abstract_domain::AbstractDomain;
+use crate::{
+    abstract_domains::abstract_domain::AbstractDomain,
+    abstract_states::abstract_state::AbstractState,
+    parser::ast::{ArithmeticCondition, ArithmeticExp},
+    propagation_algo::propagation_algo::PropagationAlgorithm,
+};
 
-#[derive(Clone, Debug, Default, PartialEq)]
+#[derive(Clone, Debug, Default)]
 pub struct State<'a, D: AbstractDomain> {
     vars: HashMap<&'a str, D>,
+    ctx: D::Context,
+}
+
+// states of the same analysis share the context
+impl<'a, D: AbstractDomain> PartialEq for State<'a, D> {
+    fn eq(&self, other: &Self) -> bool {
+        self.vars == other.vars
+    }
 }
 
 impl<'a, D: AbstractDomain> fmt::Display for State<'a, D> {
@@ -18,12 +31,17 @@ impl<'a, D: AbstractDomain> fmt::Display for State<'a, D> {
     }
 }
 
-impl<'a, 'b, D: AbstractDomain> State<'a, D> {
-    pub fn new(vars: HashMap<&'a str, D>) -> Self {
-        State { vars }
+impl<'a, D: AbstractDomain> State<'a, D> {
+    pub fn new(vars: HashMap<&'a str, D>, ctx: D::Context) -> Self {
+        State { vars, ctx }
+    }
+
+    pub fn context(&self) -> &D::Context {
+        &self.ctx
     }
 
     pub fn update(&mut self, var: &'a str, value: D) {
+        let value = value.normalize(&self.ctx);
         if value == D::bottom() {
             self.vars = HashMap::new();
         }
@@ -41,9 +59,9 @@ impl<'a, 'b, D: AbstractDomain> State<'a, D> {
 
         let mut r = self.clone();
         other.vars.iter().for_each(|(var, value)| {
-            let old_value = r.vars.insert(var, value.clone());
-            if old_value.is_some() {
-                r.vars.insert(var, old_value.unwrap().lub(value));
+            let old_value = r.vars.insert(var, *value);
+            if let Some(old_value) = old_value {
+                r.vars.insert(var, old_value.lub(value));
             }
         });
         r
@@ -57,21 +75,22 @@ impl<'a, 'b, D: AbstractDomain> State<'a, D> {
         assert!(self.vars.keys().all(|var| other.vars.contains_key(var)));
         let mut r = self.clone();
         other.vars.iter().for_each(|(var, value)| {
-            let old_value = r.vars.insert(var, value.clone());
-            if old_value.is_some() {
-                r.vars.insert(var, old_value.unwrap().glb(value));
+            let old_value = r.vars.insert(var, *value);
+            if let Some(old_value) = old_value {
+                r.vars.insert(var, old_value.glb(value));
             }
         });
         r
     }
 
-    pub fn lookup(&self, var: &'b str) -> &D {
+    pub fn lookup(&self, var: &str) -> &D {
         self.vars.get(var).unwrap()
     }
 
     pub fn bottom() -> Self {
         State {
             vars: HashMap::new(),
+            ctx: D::Context::default(),
         }
     }
 
@@ -90,9 +109,12 @@ impl<'a, 'b, D: AbstractDomain> State<'a, D> {
         let vars = self
             .vars
             .iter()
-            .map(|(var, value)| (*var, widening(value, rhs.lookup(var), thresholds)))
+            .map(|(var, value)| {
+                let widened = widening(value, rhs.lookup(var), thresholds);
+                (*var, widened.normalize(&self.ctx))
+            })
             .collect();
-        State { vars }
+        State::new(vars, self.ctx.clone())
     }
 
     pub fn narrowing(&self, rhs: &Self) -> Self {
@@ -107,10 +129,66 @@ impl<'a, 'b, D: AbstractDomain> State<'a, D> {
             .iter()
             .map(|(var, value)| (*var, value.narrowing(rhs.lookup(var))))
             .collect();
-        State { vars }
+        State::new(vars, self.ctx.clone())
     }
 
     pub fn vars(&self) -> HashSet<&'a str> {
-        self.vars.iter().map(|(key, _)| *key).collect()
+        self.vars.keys().copied().collect()
+    }
+}
+
+impl<'a, D: AbstractDomain> AbstractState<'a> for State<'a, D> {
+    type Value = D;
+    type Context = D::Context;
+
+    fn build(vars: HashSet<&'a str>, ctx: &Self::Context) -> Self {
+        let vars = vars.into_iter().map(|var| (var, D::top())).collect();
+        State::new(vars, ctx.clone())
+    }
+
+    fn bottom() -> Self {
+        State::bottom()
+    }
+
+    fn is_bottom(&self) -> bool {
+        self.vars.is_empty()
+    }
+
+    fn lub(&self, other: &Self) -> Self {
+        self.lub_var_wise(other)
+    }
+
+    fn glb(&self, other: &Self) -> Self {
+        self.glb_var_wise(other)
+    }
+
+    fn widening(&self, rhs: &Self, thresholds: &HashSet<i64>) -> Self {
+        match D::widening_operator(&self.ctx) {
+            Some(widening) => State::widening(self, rhs, thresholds, widening),
+            None => rhs.clone(),
+        }
+    }
+
+    fn narrowing(&self, rhs: &Self) -> Self {
+        State::narrowing(self, rhs)
+    }
+
+    fn value_of(&self, var: &str) -> D {
+        *self.lookup(var)
+    }
+
+    fn assign(&self, var: &'a str, value: &ArithmeticExp<'a>) -> Self {
+        let mut updated_state = self.clone();
+        updated_state.update(var, self.eval(value));
+        updated_state
+    }
+
+    fn filter(&self, cond: &ArithmeticCondition<'a>) -> Self {
+        let algo = PropagationAlgorithm::build(cond, self);
+        algo.local_iterations()
+    }
+
+    fn vars(&self) -> HashSet<&'a str> {
+        State::vars(self)
     }
 }
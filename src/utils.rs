@@ -1,48 +1,138 @@
-use std::collections::{BTreeMap, HashMap};
+use std::fmt::Display;
 
-use crate::{abstract_domains::abstract_domain::AbstractDomain, interpreter::ProgramInvariants};
+use crate::{
+    interpreter::{Annotations, Assertion, Assertions, Partitioning, Point, ProgramInvariants},
+    parser::ast::Position,
+};
 
-pub fn decorate_code_with_analysis<'a, D: AbstractDomain>(
+pub fn decorate_code_with_analysis<S: Display>(
     source_code: String,
-    mut invariants: ProgramInvariants<'a, D>,
+    mut invariants: ProgramInvariants<S>,
+    assertions: &Assertions,
+    annotations: &Annotations<S>,
 ) -> String {
     // Extract last invariant safely
     let program_inv = invariants
         .pop_last()
-        .map(|(_, inv)| format!("\n# {}", inv))
+        .map(|(_, inv)| format!("\n# {}", comment_lines(&inv.to_string(), "")))
         .unwrap_or_else(|| String::from("\n# No program invariant found"));
 
     let mut code_analysis: Vec<_> = source_code.lines().collect();
-    code_analysis.push(&program_inv);
+    let indentation = |line: usize| {
+        let code = code_analysis.get(line).copied().unwrap_or_default();
+        code[..code.len() - code.trim_start().len()].to_string()
+    };
 
-    let invariants: BTreeMap<_, _> = invariants
-        .into_iter()
-        .map(|(pos, inv)| {
-            let tabs = " ".repeat(pos.clm);
-            (pos, format!("{tabs}# LOOP INVARIANT: {inv}"))
-        })
-        .collect();
+    // the comments are inserted before a line, those about the end of the previous line first
+    let mut comments: Vec<((usize, bool, Position), String)> = vec![];
+    for (pos, inv) in &invariants {
+        let tabs = " ".repeat(pos.clm);
+        let inv = comment_lines(&inv.to_string(), &tabs);
+        comments.push((
+            (pos.line, true, pos.clone()),
+            format!("{tabs}# LOOP INVARIANT: {inv}"),
+        ));
+    }
+    for (pos, Assertion { verdict, .. }) in assertions {
+        let tabs = " ".repeat(pos.clm);
+        comments.push((
+            (pos.line, true, pos.clone()),
+            format!("{tabs}# ASSERTION {verdict}"),
+        ));
+    }
+    for ((pos, point), state) in annotations {
+        // of the statements ending on a line, only the last one is reported
+        let last_on_line = annotations
+            .range((pos.clone(), *point)..)
+            .skip(1)
+            .take_while(|((next, _), _)| next.line == pos.line)
+            .all(|((_, p), _)| *p != Point::After);
+        let (line, label) = match point {
+            Point::Then => (pos.line, "THEN: "),
+            Point::Else => (pos.line, "ELSE: "),
+            Point::After if last_on_line => (pos.line + 1, ""),
+            Point::After => continue,
+            Point::LoopExit => (pos.line + 1, "LOOP EXIT: "),
+        };
+        let tabs = indentation(pos.line);
+        let state = comment_lines(&state.to_string(), &tabs);
+        comments.push((
+            (
+                line,
+                *point == Point::Then || *point == Point::Else,
+                pos.clone(),
+            ),
+            format!("{tabs}# {label}{state}"),
+        ));
+    }
+    comments.sort_by(|(lhs, _), (rhs, _)| lhs.cmp(rhs));
 
+    code_analysis.push(&program_inv);
     // Insert invariants in reverse order to preserve correct line positions
-    for (pos, inv) in invariants.iter().rev() {
-        code_analysis.insert(pos.line, inv);
+    for ((line, _, _), comment) in comments.iter().rev() {
+        code_analysis.insert(*line, comment);
     }
     code_analysis.join("\n")
 }
 
-pub fn extract_vars_init(source_code: &String) -> HashMap<&str, &str> {
-    let assume_line = source_code.lines().next().unwrap_or_default();
-    if !assume_line.contains("assume") || assume_line.contains("#") {
-        return HashMap::new();
+// invariants spanning several lines, like disjunctions, stay inside the comment
+fn comment_lines(inv: &str, indentation: &str) -> String {
+    inv.replace('\n', &format!("\n{indentation}# "))
+}
+
+// directives such as `# @partition`, `# @unroll 3` or `# @merge` in a comment on the line of an
+// if/while keyword, the other words of a comment are ignored
+pub fn extract_partitioning(source_code: &str) -> Partitioning {
+    let mut partitioning = Partitioning::default();
+    for (line, code) in source_code.lines().enumerate() {
+        let Some((_, comment)) = code.split_once('#') else {
+            continue;
+        };
+        let mut words = comment.split_whitespace();
+        while let Some(word) = words.next() {
+            match word {
+                "@partition" => {
+                    partitioning.conditionals.insert(line);
+                }
+                "@merge" => {
+                    partitioning.merges.insert(line);
+                }
+                "@unroll" => {
+                    let n = words.next().and_then(|n| n.parse().ok()).unwrap_or(1);
+                    partitioning.unrolled_loops.insert(line, n);
+                }
+                _ => (),
+            }
+        }
+    }
+    partitioning
+}
+
+#[cfg(test)]
+mod test {
+    use super::extract_partitioning;
+
+    #[test]
+    fn partitioning_directives() {
+        let partitioning = extract_partitioning(
+            "if x < 0 then { y := 1 } else { y := 2 }; # @partition\n\
+             while x < 10 do { x := x + 1 }; # @unroll 3 @merge\n\
+             while y < 10 do { y := y + 1 } # @unroll",
+        );
+        assert!(partitioning.conditionals.contains(&0));
+        assert_eq!(partitioning.unrolled_loops.get(&1), Some(&3));
+        assert!(partitioning.merges.contains(&1));
+        assert_eq!(partitioning.unrolled_loops.get(&2), Some(&1));
+    }
+
+    #[test]
+    fn partitioning_ignores_comments() {
+        let partitioning = extract_partitioning(
+            "if x < 0 then { y := 1 } else { y := 2 }; # partition on the sign\n\
+             while x < 10 do { x := x + 1 } # merge sort step, unroll later",
+        );
+        assert!(partitioning.conditionals.is_empty());
+        assert!(partitioning.merges.is_empty());
+        assert!(partitioning.unrolled_loops.is_empty());
     }
-    source_code.lines().next().unwrap_or("assume").trim()[6..]
-        .split(';')
-        .map(|assignment| {
-            let mut parts = assignment.split(":=");
-            (
-                parts.next().unwrap().trim(),
-                parts.next().unwrap_or_default().trim(),
-            )
-        })
-        .collect::<HashMap<&str, &str>>()
 }